@@ -0,0 +1,344 @@
+extern crate proc_macro;
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Error, Fields, Ident, Lit, Meta, NestedMeta, Type, Variant};
+
+// Derives `draco::router::Route` for an enum. Every variant needs an `#[at("/posts/:id")]`
+// attribute. `:name` segments are parsed into the field named `name` (or into the next field, for
+// tuple variants). Any other named field is parsed from the query string, and is optional if its
+// type is an `Option`.
+#[proc_macro_derive(Route, attributes(at))]
+pub fn derive_route(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match route(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+enum Segment {
+    Static(String),
+    Param(String),
+}
+
+struct Binding<'a> {
+    // The name `to_url` binds the field to.
+    ident: Ident,
+    // The field's name, for named fields.
+    name: Option<&'a Ident>,
+    ty: &'a Type,
+}
+
+fn route(input: &DeriveInput) -> Result<TokenStream, Error> {
+    let ident = &input.ident;
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(Error::new(
+                input.span(),
+                "Route can only be derived for enums",
+            ))
+        }
+    };
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "Route can't be derived for generic enums",
+        ));
+    }
+
+    let mut parsers = Vec::new();
+    let mut arms = Vec::new();
+    for variant in &data.variants {
+        let segments = segments(variant)?;
+        let bindings = bindings(variant);
+        let (parser, arm) = match &variant.fields {
+            Fields::Named(_) => named(ident, variant, &segments, &bindings)?,
+            _ => unnamed(ident, variant, &segments, &bindings)?,
+        };
+        parsers.push(parser);
+        arms.push(arm);
+    }
+
+    Ok(quote! {
+        impl ::draco::router::Route for #ident {
+            fn parse_state(__state: &mut ::draco::router::State) -> Option<Self> {
+                #(
+                    {
+                        #parsers
+                        let mut cloned = __state.clone();
+                        if let Some(route) = parse(&mut cloned) {
+                            *__state = cloned;
+                            return Some(route);
+                        }
+                    }
+                )*
+                None
+            }
+
+            fn to_url(&self) -> ::draco::router::Url {
+                fn build(path: Vec<String>, query: Vec<(&str, String)>) -> ::draco::router::Url {
+                    ::draco::router::Url {
+                        path,
+                        query: query.into_iter().map(|(k, v)| (k.into(), v)).collect(),
+                        hash: None,
+                    }
+                }
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
+
+fn segments(variant: &Variant) -> Result<Vec<Segment>, Error> {
+    let mut at = None;
+    for attr in &variant.attrs {
+        match attr.parse_meta() {
+            Ok(Meta::List(ref list)) if list.ident == "at" => match list.nested.iter().next() {
+                Some(NestedMeta::Literal(Lit::Str(str))) if list.nested.len() == 1 => {
+                    at = Some(str.value())
+                }
+                _ => return Err(Error::new(attr.span(), "expected #[at(\"/path\")]")),
+            },
+            _ => {}
+        }
+    }
+    let at = at.ok_or_else(|| {
+        Error::new(
+            variant.span(),
+            format!("missing #[at(\"/path\")] on {}", variant.ident),
+        )
+    })?;
+    Ok(at
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(name) => Segment::Param(name.into()),
+            None => Segment::Static(segment.into()),
+        })
+        .collect())
+}
+
+fn bindings(variant: &Variant) -> Vec<Binding<'_>> {
+    variant
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| Binding {
+            ident: field
+                .ident
+                .clone()
+                .unwrap_or_else(|| Ident::new(&format!("field_{}", index), Span::call_site())),
+            name: field.ident.as_ref(),
+            ty: &field.ty,
+        })
+        .collect()
+}
+
+// Parses into `#[at]`'s params by name, and into the rest of the fields from the query string.
+fn named(
+    ident: &Ident,
+    variant: &Variant,
+    segments: &[Segment],
+    bindings: &[Binding],
+) -> Result<(TokenStream, TokenStream), Error> {
+    let mut statements = Vec::new();
+    let mut path = Vec::new();
+    for segment in segments {
+        match segment {
+            Segment::Static(str) => {
+                statements.push(quote!(::draco::router::Parse::parse_state(&#str, __state)?;));
+                path.push(quote!(::std::string::String::from(#str)));
+            }
+            Segment::Param(name) => {
+                let binding = bindings
+                    .iter()
+                    .find(|binding| binding.name.is_some_and(|ident| ident == name))
+                    .ok_or_else(|| {
+                        Error::new(
+                            variant.span(),
+                            format!("{} has no field named {}", variant.ident, name),
+                        )
+                    })?;
+                let (ident, ty) = (&binding.ident, binding.ty);
+                statements.push(quote! {
+                    let #ident = ::draco::router::Parse::parse_state(
+                        &::draco::router::param::<#ty>(),
+                        __state,
+                    )?;
+                });
+                path.push(quote!(#ident.to_string()));
+            }
+        }
+    }
+    statements
+        .push(quote!(::draco::router::Parse::parse_state(&::draco::router::end(), __state)?;));
+
+    let mut query = Vec::new();
+    for binding in bindings {
+        let name = binding.name.unwrap().to_string();
+        if segments.iter().any(|segment| match segment {
+            Segment::Param(param) => *param == name,
+            Segment::Static(_) => false,
+        }) {
+            continue;
+        }
+        let ident = &binding.ident;
+        match option(binding.ty) {
+            Some(ty) => {
+                statements.push(quote! {
+                    let #ident = ::draco::router::Parse::parse_state(
+                        &::draco::router::Parse::optional(::draco::router::query::<#ty>(#name)),
+                        __state,
+                    )?;
+                });
+                query.push(quote! {
+                    if let Some(value) = #ident {
+                        __query.push((#name, value.to_string()));
+                    }
+                });
+            }
+            None => {
+                let ty = binding.ty;
+                statements.push(quote! {
+                    let #ident = ::draco::router::Parse::parse_state(
+                        &::draco::router::query::<#ty>(#name),
+                        __state,
+                    )?;
+                });
+                query.push(quote!(__query.push((#name, #ident.to_string()));));
+            }
+        }
+    }
+
+    let variant = &variant.ident;
+    let idents = bindings
+        .iter()
+        .map(|binding| &binding.ident)
+        .collect::<Vec<_>>();
+    let idents = &idents;
+    let parser = quote! {
+        fn parse(__state: &mut ::draco::router::State) -> Option<#ident> {
+            #(#statements)*
+            Some(#ident::#variant { #(#idents),* })
+        }
+    };
+    let arm = quote! {
+        #ident::#variant { #(#idents),* } => {
+            let __path: Vec<String> = vec![#(#path),*];
+            let mut __query: Vec<(&str, String)> = Vec::new();
+            #(#query)*
+            build(__path, __query)
+        }
+    };
+    Ok((parser, arm))
+}
+
+// Parses into `#[at]`'s params in order.
+fn unnamed(
+    ident: &Ident,
+    variant: &Variant,
+    segments: &[Segment],
+    bindings: &[Binding],
+) -> Result<(TokenStream, TokenStream), Error> {
+    let params = segments
+        .iter()
+        .filter(|segment| match segment {
+            Segment::Param(_) => true,
+            Segment::Static(_) => false,
+        })
+        .count();
+    if params != bindings.len() {
+        return Err(Error::new(
+            variant.span(),
+            format!(
+                "{} has {} fields but its path has {} params",
+                variant.ident,
+                bindings.len(),
+                params
+            ),
+        ));
+    }
+
+    let mut bindings = bindings.iter();
+    let mut statements = Vec::new();
+    let mut path = Vec::new();
+    for segment in segments {
+        match segment {
+            Segment::Static(str) => {
+                statements.push(quote!(::draco::router::Parse::parse_state(&#str, __state)?;));
+                path.push(quote!(::std::string::String::from(#str)));
+            }
+            Segment::Param(_) => {
+                let binding = bindings.next().unwrap();
+                let (ident, ty) = (&binding.ident, binding.ty);
+                statements.push(quote! {
+                    let #ident = ::draco::router::Parse::parse_state(
+                        &::draco::router::param::<#ty>(),
+                        __state,
+                    )?;
+                });
+                path.push(quote!(#ident.to_string()));
+            }
+        }
+    }
+    statements
+        .push(quote!(::draco::router::Parse::parse_state(&::draco::router::end(), __state)?;));
+
+    let variant_ident = &variant.ident;
+    let idents = variant
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(index, _)| Ident::new(&format!("field_{}", index), Span::call_site()))
+        .collect::<Vec<_>>();
+    let idents = &idents;
+    let (pattern, value) = match variant.fields {
+        Fields::Unit => (
+            quote!(#ident::#variant_ident),
+            quote!(#ident::#variant_ident),
+        ),
+        _ => (
+            quote!(#ident::#variant_ident(#(#idents),*)),
+            quote!(#ident::#variant_ident(#(#idents),*)),
+        ),
+    };
+    let parser = quote! {
+        fn parse(__state: &mut ::draco::router::State) -> Option<#ident> {
+            #(#statements)*
+            Some(#value)
+        }
+    };
+    let arm = quote! {
+        #pattern => {
+            let __path: Vec<String> = vec![#(#path),*];
+            build(__path, Vec::new())
+        }
+    };
+    Ok((parser, arm))
+}
+
+// Returns `T` if `ty` is `Option<T>`.
+fn option(ty: &Type) -> Option<&Type> {
+    let path = match ty {
+        Type::Path(path) if path.qself.is_none() => &path.path,
+        _ => return None,
+    };
+    let segment = path.segments.iter().last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(arguments) if arguments.args.len() == 1 => {
+            match arguments.args.iter().next() {
+                Some(syn::GenericArgument::Type(ty)) => Some(ty),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -39,7 +39,8 @@ pub fn start() {
     draco::start(
         Counter::default(),
         draco::select("main").expect("main").into(),
-    );
+    )
+    .forget();
 }
 
 pub fn main() {}
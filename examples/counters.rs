@@ -88,7 +88,8 @@ pub fn start() {
     draco::start(
         Counters::default(),
         draco::select("main").expect("main").into(),
-    );
+    )
+    .forget();
 }
 
 pub fn main() {}
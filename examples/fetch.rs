@@ -56,7 +56,7 @@ impl draco::App for Fetch {
 
 #[wasm_bindgen]
 pub fn start() {
-    draco::start(Fetch::new(), draco::select("main").expect("main").into());
+    draco::start(Fetch::new(), draco::select("main").expect("main").into()).forget();
 }
 
 pub fn main() {}
@@ -117,7 +117,7 @@ impl draco::App for Form {
 
 #[wasm_bindgen]
 pub fn start() {
-    draco::start(Form::default(), draco::select("main").expect("main").into());
+    draco::start(Form::default(), draco::select("main").expect("main").into()).forget();
 }
 
 pub fn main() {}
@@ -12,7 +12,7 @@ impl draco::App for HelloWorld {
 
 #[wasm_bindgen]
 pub fn start() {
-    draco::start(HelloWorld, draco::select("main").expect("main").into());
+    draco::start(HelloWorld, draco::select("main").expect("main").into()).forget();
 }
 
 pub fn main() {}
@@ -16,7 +16,8 @@ pub fn start() {
     draco::start(
         Jfb::new(!non_keyed),
         draco::select("main").expect("main").into(),
-    );
+    )
+    .forget();
 }
 
 pub struct Jfb {
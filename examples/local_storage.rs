@@ -69,7 +69,8 @@ pub fn start() {
     draco::start(
         LocalStorage::new(),
         draco::select("main").expect("main").into(),
-    );
+    )
+    .forget();
 }
 
 pub fn main() {}
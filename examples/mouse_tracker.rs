@@ -57,7 +57,8 @@ pub fn start() {
     draco::start(
         MouseTracker::default(),
         draco::select("main").expect("main").into(),
-    );
+    )
+    .forget();
 }
 
 pub fn main() {}
@@ -5,20 +5,17 @@ struct Router {
     url: Option<draco::router::Url>,
     route: Option<Route>,
     subscription: Option<draco::Unsubscribe>,
+    guard: Option<draco::router::Guard>,
 }
 
 enum Message {
     Subscribe,
     Navigate(draco::router::Url),
+    ToggleGuard,
+    Confirm(draco::router::Navigation),
     NoOp,
 }
 
-impl Default for Message {
-    fn default() -> Self {
-        Message::NoOp
-    }
-}
-
 #[derive(Debug)]
 enum Route {
     Index,
@@ -28,7 +25,7 @@ enum Route {
 
 impl Route {
     fn new(url: &draco::router::Url) -> Option<Self> {
-        use draco::router::*;
+        use draco::router::{param, parse, query, Parse};
         parse(url)
             // /
             .alt((), |()| Route::Index)
@@ -53,22 +50,41 @@ impl draco::App for Router {
     fn update(&mut self, mailbox: &draco::Mailbox<Message>, message: Self::Message) {
         match message {
             Message::Subscribe => {
-                self.subscription = Some(mailbox.subscribe(
-                    draco::router::Router::new(draco::router::Mode::Hash),
-                    Message::Navigate,
-                ));
+                self.subscription = Some(
+                    mailbox.subscribe(
+                        draco::router::Router::new(draco::router::Mode::Hash)
+                            .hash_prefix("!")
+                            .restore_scroll(true),
+                        Message::Navigate,
+                    ),
+                );
             }
             Message::Navigate(url) => {
                 self.route = Route::new(&url);
                 self.url = Some(url);
             }
+            Message::ToggleGuard => {
+                self.guard = match self.guard.take() {
+                    Some(_) => None,
+                    None => Some(draco::router::block(mailbox, Message::Confirm)),
+                };
+            }
+            Message::Confirm(navigation) => {
+                let confirmed = web_sys::window()
+                    .unwrap()
+                    .confirm_with_message(&format!("Leave for {}?", navigation.url))
+                    .unwrap_or(false);
+                if confirmed {
+                    self.guard = None;
+                    navigation.resume();
+                }
+            }
             Message::NoOp => {}
         }
     }
 
     fn render(&self) -> draco::Node<Self::Message> {
         use draco::html as h;
-        use draco::router::Mode::Hash;
         let links = [
             "/",
             "/posts",
@@ -83,11 +99,32 @@ impl draco::App for Router {
         h::div()
             .push(h::div().push(format!("Url: {:?}", &self.url)))
             .push(h::div().push(format!("Route: {:?}", &self.route)))
+            .push(
+                h::div()
+                    .push(h::button().push("Back").on("click", |_| {
+                        draco::router::back();
+                        Message::NoOp
+                    }))
+                    .push(h::button().push("Forward").on("click", |_| {
+                        draco::router::forward();
+                        Message::NoOp
+                    })),
+            )
+            .push(
+                h::label()
+                    .push(
+                        h::input()
+                            .attr("type", "checkbox")
+                            .checked(self.guard.is_some())
+                            .on("change", |_| Message::ToggleGuard),
+                    )
+                    .push(" Confirm before navigating"),
+            )
             .append(links.iter().map(|link| {
                 h::h2()
                     .push(
                         h::span().push(
-                            draco::router::link(Hash, link)
+                            draco::router::nav_link(*link)
                                 .push(link.to_string())
                                 .attr("style", "margin-right: .5rem;"),
                         ),
@@ -95,14 +132,14 @@ impl draco::App for Router {
                     .push(h::button().push("Push").on("click", {
                         let link = link.clone();
                         move |_| {
-                            draco::router::push(Hash, link);
+                            draco::router::push(link);
                             Message::NoOp
                         }
                     }))
                     .push(h::button().push("Replace").on("click", {
                         let link = link.clone();
                         move |_| {
-                            draco::router::replace(Hash, link);
+                            draco::router::replace(link);
                             Message::NoOp
                         }
                     }))
@@ -113,11 +150,12 @@ impl draco::App for Router {
 
 #[wasm_bindgen]
 pub fn start() {
-    let mailbox = draco::start(
+    let instance = draco::start(
         Router::default(),
         draco::select("main").expect("main").into(),
     );
-    mailbox.send(Message::Subscribe);
+    instance.mailbox().send(Message::Subscribe);
+    instance.forget();
 }
 
 pub fn main() {}
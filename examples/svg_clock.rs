@@ -29,8 +29,9 @@ impl draco::App for Clock {
                 if let Some(_) = self.subscription.take() {
                 } else {
                     self.subscription = Some(
-                        mailbox
-                            .subscribe(draco::subscription::Interval::new(16), |()| Message::Tick),
+                        mailbox.subscribe(draco::subscription::AnimationFrame::new(), |_| {
+                            Message::Tick
+                        }),
                     );
                 }
             }
@@ -105,8 +106,9 @@ impl draco::App for Clock {
 
 #[wasm_bindgen]
 pub fn start() {
-    let mailbox = draco::start(Clock::new(), draco::select("main").expect("main").into());
-    mailbox.send(Message::Toggle);
+    let instance = draco::start(Clock::new(), draco::select("main").expect("main").into());
+    instance.mailbox().send(Message::Toggle);
+    instance.forget();
 }
 
 pub fn main() {}
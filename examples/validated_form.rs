@@ -0,0 +1,114 @@
+use draco::form::{self, Field};
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug)]
+pub struct ValidatedForm {
+    username: Field,
+    email: Field,
+    bio: Field,
+}
+
+impl ValidatedForm {
+    fn new() -> Self {
+        ValidatedForm {
+            username: Field::new("username")
+                .validate(form::required())
+                .validate(form::min_length(3))
+                .validate(form::max_length(16)),
+            email: Field::new("email")
+                .validate(form::required())
+                .validate(form::regex("^[^@\\s]+@[^@\\s]+$").message("is not an email address")),
+            bio: Field::new("bio")
+                .initial("Hello!")
+                .validate(form::custom(|value| {
+                    if value.contains("spam") {
+                        Err("must not contain spam".into())
+                    } else {
+                        Ok(())
+                    }
+                })),
+        }
+    }
+
+    fn fields(&self) -> [&Field; 3] {
+        [&self.username, &self.email, &self.bio]
+    }
+}
+
+pub enum Message {
+    Username(form::Message),
+    Email(form::Message),
+    Bio(form::Message),
+    Submit,
+}
+
+impl draco::App for ValidatedForm {
+    type Message = Message;
+
+    fn update(&mut self, _: &draco::Mailbox<Message>, message: Self::Message) {
+        use self::Message::*;
+        match message {
+            Username(message) => self.username.update(message),
+            Email(message) => self.email.update(message),
+            Bio(message) => self.bio.update(message),
+            Submit => {
+                for field in &mut [&mut self.username, &mut self.email, &mut self.bio] {
+                    field.touch();
+                }
+                if form::is_valid(&self.fields()) {
+                    web_sys::window()
+                        .unwrap()
+                        .alert_with_message(&form::to_query_string(&self.fields()))
+                        .unwrap();
+                }
+            }
+        }
+    }
+
+    fn render(&self) -> draco::Node<Self::Message> {
+        use draco::html as h;
+
+        fn row(
+            label: &str,
+            field: &Field,
+            input: draco::NonKeyedElement<Message>,
+        ) -> draco::NonKeyedElement<Message> {
+            h::p()
+                .push(h::label().attr("for", field.name().to_string()).push(label))
+                .push(input.attr("id", field.name().to_string()))
+                .append(
+                    field
+                        .visible_errors()
+                        .iter()
+                        .map(|error| h::small().push(format!(" {} {}", label, error))),
+                )
+        }
+
+        h::form()
+            .on("submit", |event| {
+                event.prevent_default();
+                Message::Submit
+            })
+            .push(row(
+                "Username",
+                &self.username,
+                self.username.input(Message::Username),
+            ))
+            .push(row("Email", &self.email, self.email.input(Message::Email)))
+            .push(row("Bio", &self.bio, self.bio.textarea(Message::Bio)))
+            .push(h::pre().push(form::to_query_string(&self.fields())))
+            .push(h::button().push("Submit"))
+            .into()
+    }
+}
+
+#[wasm_bindgen]
+pub fn start() {
+    draco::start(
+        ValidatedForm::new(),
+        draco::select("main").expect("main").into(),
+    )
+    .forget();
+}
+
+pub fn main() {}
@@ -10,12 +10,17 @@ pub trait App: Sized + 'static {
     fn render(&self) -> Node<Self::Message>;
 }
 
+// Unmounts the app when dropped. Use `forget` to keep it running for the rest of the page's
+// lifetime instead.
+#[must_use = "dropping the Instance unmounts the app; call .forget() to keep it running"]
 pub struct Instance<A: App> {
     inner: Rc<Inner<A>>,
 }
 
 struct Inner<A: App> {
-    app: RefCell<A>,
+    // `None` once unmounted.
+    app: RefCell<Option<A>>,
+    root: web::Node,
     node: RefCell<web::Node>,
     vnode: RefCell<Node<A::Message>>,
     queue: RefCell<Vec<A::Message>>,
@@ -23,42 +28,40 @@ struct Inner<A: App> {
 }
 
 impl<A: App> Instance<A> {
-    fn send(&self, message: A::Message) {
-        if *self.inner.is_updating.borrow() {
-            self.inner.queue.borrow_mut().push(message);
-            return;
-        }
-        self.inner.is_updating.replace(true);
-        let mailbox = self.mailbox();
-        self.inner.app.borrow_mut().update(&mailbox, message);
-        while !self.inner.queue.borrow().is_empty() {
-            let message = self.inner.queue.borrow_mut().remove(0);
-            self.inner.app.borrow_mut().update(&mailbox, message);
-        }
-        self.inner.is_updating.replace(false);
-        self.render();
+    pub fn mailbox(&self) -> Mailbox<A::Message> {
+        Inner::mailbox(&self.inner)
     }
 
-    fn render(&self) {
-        let mut new_vnode = self.inner.app.borrow().render();
-        let new_node = new_vnode.patch(&mut self.inner.vnode.borrow_mut(), self.mailbox());
-        self.inner.vnode.replace(new_vnode);
-        self.inner.node.replace(new_node);
+    // Panics if called while the app is updating or rendering.
+    pub fn with_app<T>(&self, f: impl FnOnce(&A) -> T) -> T {
+        f(self.inner.app.borrow().as_ref().expect("app"))
     }
 
-    fn mailbox(&self) -> Mailbox<A::Message> {
-        let cloned = self.clone();
-        Mailbox::new(move |message| {
-            cloned.send(message);
-        })
+    // Same as dropping the instance.
+    pub fn unmount(self) {}
+
+    // Like `Closure::forget`.
+    pub fn forget(self) {
+        std::mem::forget(self)
     }
 }
 
-impl<A: App> std::clone::Clone for Instance<A> {
-    fn clone(&self) -> Self {
-        Instance {
-            inner: Rc::clone(&self.inner),
+impl<A: App> Drop for Instance<A> {
+    // Puts back the node the app was started on, detaches all listeners and drops the app along
+    // with its subscriptions. Messages sent afterwards are ignored.
+    fn drop(&mut self) {
+        let inner = &self.inner;
+        inner.app.replace(None);
+        inner.queue.borrow_mut().clear();
+        let mut vnode = inner.vnode.borrow_mut();
+        vnode.detach();
+        let node = inner.node.borrow();
+        if let Some(parent_node) = node.parent_node() {
+            parent_node
+                .replace_child(&inner.root, &node)
+                .expect("replace_child");
         }
+        *vnode = Text::new("").into();
     }
 }
 
@@ -68,22 +71,67 @@ impl<A: App> std::fmt::Debug for Instance<A> {
     }
 }
 
-pub fn start<A: App>(app: A, node: web::Node) -> Mailbox<A::Message> {
+impl<A: App> Inner<A> {
+    fn send(this: &Rc<Self>, message: A::Message) {
+        if *this.is_updating.borrow() {
+            this.queue.borrow_mut().push(message);
+            return;
+        }
+        if this.app.borrow().is_none() {
+            return;
+        }
+        this.is_updating.replace(true);
+        let mailbox = Inner::mailbox(this);
+        if let Some(app) = this.app.borrow_mut().as_mut() {
+            app.update(&mailbox, message);
+        }
+        while !this.queue.borrow().is_empty() {
+            let message = this.queue.borrow_mut().remove(0);
+            if let Some(app) = this.app.borrow_mut().as_mut() {
+                app.update(&mailbox, message);
+            }
+        }
+        this.is_updating.replace(false);
+        Inner::render(this);
+    }
+
+    fn render(this: &Rc<Self>) {
+        let mut new_vnode = match this.app.borrow().as_ref() {
+            Some(app) => app.render(),
+            None => return,
+        };
+        let new_node = new_vnode.patch(&mut this.vnode.borrow_mut(), Inner::mailbox(this));
+        this.vnode.replace(new_vnode);
+        this.node.replace(new_node);
+    }
+
+    // Listeners and subscriptions only hold a weak reference to the instance, so that they don't
+    // keep it alive after the `Instance` is dropped.
+    fn mailbox(this: &Rc<Self>) -> Mailbox<A::Message> {
+        let inner = Rc::downgrade(this);
+        Mailbox::new(move |message| {
+            if let Some(inner) = inner.upgrade() {
+                Inner::send(&inner, message);
+            }
+        })
+    }
+}
+
+pub fn start<A: App>(app: A, node: web::Node) -> Instance<A> {
     let mut vnode = Text::new("!");
     let new_node = vnode.create().into();
     node.parent_node()
         .unwrap()
         .replace_child(&new_node, &node)
         .unwrap();
-    let instance = Instance {
-        inner: Rc::new(Inner {
-            app: RefCell::new(app),
-            node: RefCell::new(new_node),
-            vnode: RefCell::new(vnode.into()),
-            is_updating: RefCell::new(false),
-            queue: RefCell::new(Vec::new()),
-        }),
-    };
-    instance.render();
-    instance.mailbox()
+    let inner = Rc::new(Inner {
+        app: RefCell::new(Some(app)),
+        root: node,
+        node: RefCell::new(new_node),
+        vnode: RefCell::new(vnode.into()),
+        is_updating: RefCell::new(false),
+        queue: RefCell::new(Vec::new()),
+    });
+    Inner::render(&inner);
+    Instance { inner }
 }
@@ -32,6 +32,15 @@ struct Attr {
 }
 
 impl Attr {
+    // Properties are applied after the children so that e.g. a `select`'s `value` can refer to
+    // `option`s which don't exist yet during the attribute pass.
+    fn is_property(&self) -> bool {
+        match &*self.name {
+            "checked" | "selected" | "value" => true,
+            _ => false,
+        }
+    }
+
     fn patch(&self, old_value: Option<&str>, element: &web::Element) {
         match &*self.name {
             "checked" => {
@@ -43,6 +52,15 @@ impl Attr {
                     return;
                 }
             }
+            "selected" => {
+                if let Some(option) = element.dyn_ref::<web::HtmlOptionElement>() {
+                    let selected = &self.value == "true";
+                    if option.selected() != selected {
+                        option.set_selected(selected);
+                    }
+                    return;
+                }
+            }
             "value" => {
                 if let Some(input) = element.dyn_ref::<web::HtmlInputElement>() {
                     if input.value() != self.value {
@@ -56,6 +74,12 @@ impl Attr {
                     }
                     return;
                 }
+                if let Some(select) = element.dyn_ref::<web::HtmlSelectElement>() {
+                    if select.value() != self.value {
+                        select.set_value(&self.value);
+                    }
+                    return;
+                }
             }
             _ => {}
         }
@@ -69,7 +93,7 @@ impl Attr {
 
 struct Listener<Message> {
     name: S,
-    handler: Option<Box<FnMut(web::Event) -> Message>>,
+    handler: Option<Box<FnMut(web::Event) -> Option<Message>>>,
     closure: Option<Closure<FnMut(web::Event)>>,
 }
 
@@ -83,12 +107,15 @@ impl<Message: 'static> Listener<Message> {
             mut handler,
             closure,
         } = self;
-        let handler =
-            match handler.take() {
-                Some(mut handler) => Some(Box::new(move |event| f(handler(event)))
-                    as Box<FnMut(web::Event) -> NewMessage>),
-                None => None,
-            };
+        let handler = match handler.take() {
+            Some(mut handler) => {
+                Some(
+                    Box::new(move |event| handler(event).map(|message| f(message)))
+                        as Box<FnMut(web::Event) -> Option<NewMessage>>,
+                )
+            }
+            None => None,
+        };
         Listener {
             name,
             handler,
@@ -139,6 +166,17 @@ where
         self.attr("checked", if checked { "true" } else { "false" })
     }
 
+    pub fn selected(self, selected: bool) -> Self {
+        self.attr("selected", if selected { "true" } else { "false" })
+    }
+
+    pub fn radio<N: Into<S>, V: Into<S>>(self, name: N, value: V, checked: bool) -> Self {
+        self.attr("type", "radio")
+            .attr("name", name)
+            .attr("value", value)
+            .checked(checked)
+    }
+
     pub fn class(mut self, str: &str) -> Self {
         if !self.class.is_empty() {
             self.class += " ";
@@ -148,9 +186,18 @@ where
     }
 
     pub fn on<N: Into<S>>(
+        self,
+        name: N,
+        mut handler: impl FnMut(web::Event) -> C::Message + 'static,
+    ) -> Self {
+        self.on_maybe(name, move |event| Some(handler(event)))
+    }
+
+    // Like `on`, but the handler may decide not to send a message for an event.
+    pub fn on_maybe<N: Into<S>>(
         mut self,
         name: N,
-        handler: impl FnMut(web::Event) -> C::Message + 'static,
+        handler: impl FnMut(web::Event) -> Option<C::Message> + 'static,
     ) -> Self {
         self.listeners.push(Listener {
             name: name.into(),
@@ -188,6 +235,70 @@ where
         })
     }
 
+    pub fn on_select_multiple(
+        self,
+        mut handler: impl FnMut(Vec<String>) -> C::Message + 'static,
+    ) -> Self {
+        self.on("input", move |event| {
+            let mut values = Vec::new();
+            if let Some(target) = event.target() {
+                if let Some(select) = target.dyn_ref::<web::HtmlSelectElement>() {
+                    let options = select.selected_options();
+                    for index in 0..options.length() {
+                        if let Some(option) = options
+                            .item(index)
+                            .and_then(|option| option.dyn_into::<web::HtmlOptionElement>().ok())
+                        {
+                            values.push(option.value());
+                        }
+                    }
+                }
+            }
+            handler(values)
+        })
+    }
+
+    pub fn on_radio(self, mut handler: impl FnMut(String) -> C::Message + 'static) -> Self {
+        self.on("change", move |event| {
+            if let Some(target) = event.target() {
+                if let Some(input) = target.dyn_ref::<web::HtmlInputElement>() {
+                    return handler(input.value());
+                }
+            }
+            return handler("".into());
+        })
+    }
+
+    pub fn on_files(self, mut handler: impl FnMut(Vec<web::File>) -> C::Message + 'static) -> Self {
+        self.on("change", move |event| {
+            if let Some(target) = event.target() {
+                if let Some(input) = target.dyn_ref::<web::HtmlInputElement>() {
+                    return handler(crate::file::files(input.files()));
+                }
+            }
+            return handler(Vec::new());
+        })
+    }
+
+    pub fn on_drop_files(
+        self,
+        mut handler: impl FnMut(Vec<web::File>) -> C::Message + 'static,
+    ) -> Self {
+        // The default action of `dragover` has to be prevented for `drop` to fire at all.
+        self.on_maybe("dragover", |event| {
+            event.prevent_default();
+            None
+        })
+        .on("drop", move |event| {
+            event.prevent_default();
+            let files = event
+                .dyn_ref::<web::DragEvent>()
+                .and_then(|event| event.data_transfer())
+                .and_then(|data_transfer| data_transfer.files());
+            handler(crate::file::files(files))
+        })
+    }
+
     pub fn create(&mut self, mailbox: Mailbox<C::Message>) -> web::Element {
         let document = web::window().expect("window").document().expect("document");
 
@@ -198,7 +309,7 @@ where
                 .expect("create_element_ns"),
         };
 
-        for attr in &self.attrs {
+        for attr in self.attrs.iter().filter(|attr| !attr.is_property()) {
             attr.patch(None, &node);
         }
 
@@ -213,6 +324,10 @@ where
 
         self.children.create(node.as_ref() as &web::Node, mailbox);
 
+        for attr in self.attrs.iter().filter(|attr| attr.is_property()) {
+            attr.patch(None, &node);
+        }
+
         self.node = Some(node.clone());
         node
     }
@@ -229,13 +344,8 @@ where
             return new_node;
         }
 
-        for attr in &self.attrs {
-            let old_attr = old
-                .attrs
-                .iter()
-                .find(|old_attr| old_attr.name == attr.name)
-                .map(|attr| &*attr.value);
-            attr.patch(old_attr, &old_node);
+        for attr in self.attrs.iter().filter(|attr| !attr.is_property()) {
+            attr.patch(old.attr_value(&attr.name), &old_node);
         }
 
         for old_attr in &old.attrs {
@@ -267,6 +377,10 @@ where
         self.children
             .patch(&mut old.children, old_node.as_ref(), mailbox.clone());
 
+        for attr in self.attrs.iter().filter(|attr| attr.is_property()) {
+            attr.patch(old.attr_value(&attr.name), &old_node);
+        }
+
         self.node = Some(old_node.clone());
 
         old_node
@@ -275,6 +389,26 @@ where
     pub fn node(&self) -> Option<web::Element> {
         self.node.clone()
     }
+
+    // Removes the event listeners of this element and its children and drops their closures.
+    pub(crate) fn detach(&mut self) {
+        if let Some(node) = &self.node {
+            for listener in &mut self.listeners {
+                if listener.closure.is_some() {
+                    listener.detach(node);
+                    listener.closure = None;
+                }
+            }
+        }
+        self.children.detach();
+    }
+
+    fn attr_value(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|attr| attr.name == name)
+            .map(|attr| &*attr.value)
+    }
 }
 
 impl<Message: 'static> NonKeyedElement<Message> {
@@ -395,6 +529,7 @@ pub trait Children {
     fn new() -> Self;
     fn create(&mut self, node: &web::Node, mailbox: Mailbox<Self::Message>);
     fn patch(&mut self, old: &mut Self, old_node: &web::Node, mailbox: Mailbox<Self::Message>);
+    fn detach(&mut self);
 }
 
 impl<Message: 'static> Children for NonKeyed<Message> {
@@ -429,6 +564,12 @@ impl<Message: 'static> Children for NonKeyed<Message> {
                 .expect("old_node.append_child");
         }
     }
+
+    fn detach(&mut self) {
+        for child in &mut self.0 {
+            child.detach();
+        }
+    }
 }
 
 impl<Message: 'static> Children for Keyed<Message> {
@@ -515,6 +656,12 @@ impl<Message: 'static> Children for Keyed<Message> {
             old[*index - skip].1.remove();
         }
     }
+
+    fn detach(&mut self) {
+        for (_, child) in &mut self.0 {
+            child.detach();
+        }
+    }
 }
 
 impl<Message> std::fmt::Debug for Listener<Message> {
@@ -528,10 +675,11 @@ impl<Message> std::fmt::Debug for Listener<Message> {
 impl<Message: 'static> Listener<Message> {
     fn attach(&mut self, element: &web::Element, mailbox: Mailbox<Message>) {
         let mut handler = self.handler.take().unwrap();
-        let closure = Closure::wrap(
-            Box::new(move |event: web::Event| mailbox.send(handler(event)))
-                as Box<FnMut(web::Event) + 'static>,
-        );
+        let closure = Closure::wrap(Box::new(move |event: web::Event| {
+            if let Some(message) = handler(event) {
+                mailbox.send(message);
+            }
+        }) as Box<FnMut(web::Event) + 'static>);
         (element.as_ref() as &web::EventTarget)
             .add_event_listener_with_callback(&self.name, closure.as_ref().unchecked_ref())
             .expect("add_event_listener_with_callback");
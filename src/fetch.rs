@@ -1,12 +1,44 @@
-use futures::Future;
+use crate::timer;
+use futures::{Future, Poll};
+use js_sys as js;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen_futures::JsFuture;
 use web_sys as web;
 
-#[derive(Debug)]
+mod mock;
+mod retry;
+
+pub use self::mock::Mock;
+pub use self::retry::Retry;
+
+pub use web_sys::{
+    RequestCache as Cache, RequestCredentials as Credentials, RequestMode as Mode,
+    RequestRedirect as Redirect,
+};
+
+#[derive(Clone, Debug)]
 pub struct Request {
     method: String,
     url: String,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+    body: Option<Body>,
+    credentials: Option<Credentials>,
+    mode: Option<Mode>,
+    cache: Option<Cache>,
+    redirect: Option<Redirect>,
+    referrer: Option<String>,
+    timeout: Option<i32>,
+    retry: Option<Retry>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Body {
+    Text(String),
+    Bytes(Vec<u8>),
+    Form(web::FormData),
 }
 
 impl Request {
@@ -14,30 +46,484 @@ impl Request {
         Request {
             method: method.into(),
             url: url.into(),
+            query: Vec::new(),
+            headers: Vec::new(),
+            body: None,
+            credentials: None,
+            mode: None,
+            cache: None,
+            redirect: None,
+            referrer: None,
+            timeout: None,
+            retry: None,
+        }
+    }
+
+    pub fn header<K: Into<String>, V: Into<String>>(mut self, name: K, value: V) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn query<K: Into<String>, V: ToString>(mut self, name: K, value: V) -> Self {
+        self.query.push((name.into(), value.to_string()));
+        self
+    }
+
+    pub fn query_all<K: Into<String>, V: ToString, I: IntoIterator<Item = (K, V)>>(
+        mut self,
+        i: I,
+    ) -> Self {
+        self.query.extend(
+            i.into_iter()
+                .map(|(name, value)| (name.into(), value.to_string())),
+        );
+        self
+    }
+
+    pub fn body_text<B: Into<String>>(mut self, body: B) -> Self {
+        self.body = Some(Body::Text(body.into()));
+        self
+    }
+
+    pub fn body_bytes<B: Into<Vec<u8>>>(mut self, body: B) -> Self {
+        self.body = Some(Body::Bytes(body.into()));
+        self
+    }
+
+    pub fn body_form(mut self, body: web::FormData) -> Self {
+        self.body = Some(Body::Form(body));
+        self
+    }
+
+    pub fn credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    pub fn cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn redirect(mut self, redirect: Redirect) -> Self {
+        self.redirect = Some(redirect);
+        self
+    }
+
+    pub fn referrer<R: Into<String>>(mut self, referrer: R) -> Self {
+        self.referrer = Some(referrer.into());
+        self
+    }
+
+    pub fn timeout(mut self, ms: i32) -> Self {
+        self.timeout = Some(ms);
+        self
+    }
+
+    pub fn retry(mut self, retry: Retry) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    pub fn url(&self) -> String {
+        if self.query.is_empty() {
+            return self.url.clone();
+        }
+        let query = self
+            .query
+            .iter()
+            .map(|(name, value)| format!("{}={}", encode(name), encode(value)))
+            .collect::<Vec<_>>()
+            .join("&");
+        let separator = if self.url.contains('?') { '&' } else { '?' };
+        match self.url.find('#') {
+            Some(index) => format!(
+                "{}{}{}{}",
+                &self.url[..index],
+                separator,
+                query,
+                &self.url[index..]
+            ),
+            None => format!("{}{}{}", self.url, separator, query),
+        }
+    }
+
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    pub fn body(&self) -> Option<&Body> {
+        self.body.as_ref()
+    }
+
+    pub fn send<R: Response>(self) -> Pending<R::Item> {
+        let transport = TRANSPORT.with(|transport| transport.borrow().clone());
+        self.send_with::<R>(transport)
+    }
+
+    pub fn send_with<R: Response>(mut self, transport: Rc<Transport>) -> Pending<R::Item> {
+        if let Some(retry) = self.retry.take() {
+            return retry::send::<R>(self, transport, retry);
         }
+        let Pending { future, abort } = transport.send(self);
+        Pending {
+            future: Box::new(future.and_then(R::receive)),
+            abort,
+        }
+    }
+}
+
+pub trait Transport {
+    fn send(&self, request: Request) -> Pending<RawResponse>;
+}
+
+thread_local! {
+    static TRANSPORT: RefCell<Rc<Transport>> = RefCell::new(Rc::new(Browser));
+}
+
+// Replaces the transport used by `Request::send` on the current thread, e.g. with a `Mock`.
+pub fn set_transport(transport: impl Transport + 'static) {
+    TRANSPORT.with(|current| current.replace(Rc::new(transport)));
+}
+
+#[derive(Debug, Default)]
+pub struct Browser;
+
+impl Transport for Browser {
+    fn send(&self, request: Request) -> Pending<RawResponse> {
+        let controller = web::AbortController::new().ok();
+        let timed_out = Rc::new(Cell::new(false));
+        let timeout = Rc::new(RefCell::new(None));
+        let ms = request.timeout;
+        let future = futures::future::result(build(&request, controller.as_ref()))
+            .map_err(Error::Request)
+            .and_then({
+                let controller = controller.clone();
+                let timed_out = timed_out.clone();
+                let timeout = timeout.clone();
+                move |request| {
+                    // The fetch only starts once the future is polled, so the timeout starts
+                    // here too.
+                    if let (Some(ms), Some(controller)) = (ms, controller) {
+                        timeout.replace(Some(timer::Timeout::new(ms, move || {
+                            timed_out.set(true);
+                            controller.abort();
+                        })));
+                    }
+                    JsFuture::from(web::window().unwrap().fetch_with_request(&request))
+                        .map_err(Error::from)
+                }
+            })
+            .and_then(|response| {
+                assert!(response.is_instance_of::<web::Response>());
+                let response = response.dyn_into::<web::Response>().unwrap();
+                let status = response.status();
+                let status_text = response.status_text();
+                let headers = headers(&response.headers());
+                futures::future::result(response.array_buffer())
+                    .and_then(JsFuture::from)
+                    .map_err(Error::from)
+                    .map(move |buffer| RawResponse {
+                        status,
+                        status_text,
+                        headers,
+                        body: bytes(&js::Uint8Array::new(&buffer)),
+                    })
+            })
+            .then(move |result| {
+                // This clears the timeout once the request is done.
+                timeout.replace(None);
+                match result {
+                    Err(Error::Aborted) if timed_out.get() => Err(Error::Timeout),
+                    result => result,
+                }
+            });
+        let pending = Pending::new(future);
+        match controller {
+            Some(controller) => pending.on_abort(move || controller.abort()),
+            None => pending,
+        }
+    }
+}
+
+fn build(
+    request: &Request,
+    controller: Option<&web::AbortController>,
+) -> Result<web::Request, JsValue> {
+    let mut init = web::RequestInit::new();
+    init.method(&request.method);
+    if let Some(controller) = controller {
+        init.signal(Some(&controller.signal()));
+    }
+    if !request.headers.is_empty() {
+        let headers = web::Headers::new()?;
+        for (name, value) in &request.headers {
+            headers.append(name, value)?;
+        }
+        init.headers(&headers);
+    }
+    match &request.body {
+        Some(Body::Text(text)) => {
+            init.body(Some(&JsValue::from_str(text)));
+        }
+        Some(Body::Bytes(bytes)) => {
+            init.body(Some(&uint8_array(bytes)));
+        }
+        Some(Body::Form(form)) => {
+            init.body(Some(form));
+        }
+        None => {}
+    }
+    if let Some(credentials) = request.credentials {
+        init.credentials(credentials);
+    }
+    if let Some(mode) = request.mode {
+        init.mode(mode);
+    }
+    if let Some(cache) = request.cache {
+        init.cache(cache);
+    }
+    if let Some(redirect) = request.redirect {
+        init.redirect(redirect);
+    }
+    if let Some(referrer) = &request.referrer {
+        init.referrer(referrer);
+    }
+    web::Request::new_with_str_and_init(&request.url(), &init)
+}
+
+fn headers(headers: &web::Headers) -> Vec<(String, String)> {
+    let mut vec = Vec::new();
+    if let Ok(Some(entries)) = js::try_iter(headers) {
+        for entry in entries {
+            if let Ok(entry) = entry {
+                let entry = js::Array::from(&entry);
+                if let (Some(name), Some(value)) =
+                    (entry.get(0).as_string(), entry.get(1).as_string())
+                {
+                    vec.push((name, value));
+                }
+            }
+        }
+    }
+    vec
+}
+
+pub(crate) fn bytes(array: &js::Uint8Array) -> Vec<u8> {
+    let mut bytes = vec![0; array.length() as usize];
+    array.copy_to(&mut bytes);
+    bytes
+}
+
+pub(crate) fn uint8_array(bytes: &[u8]) -> js::Uint8Array {
+    // `view` is only valid until the next allocation, so copy it into a new array.
+    js::Uint8Array::new(unsafe { &js::Uint8Array::view(bytes) })
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RawResponse {
+    pub status: u16,
+    pub status_text: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl RawResponse {
+    pub fn new(status: u16) -> Self {
+        RawResponse {
+            status,
+            ..Default::default()
+        }
+    }
+
+    pub fn header<K: Into<String>, V: Into<String>>(mut self, name: K, value: V) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn body<B: Into<Vec<u8>>>(mut self, body: B) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.status >= 200 && self.status < 300
     }
 
-    pub fn send<R: Response>(self) -> impl Future<Item = R::Item, Error = Error> {
-        let mut init = web::RequestInit::new();
-        init.method(&self.method);
-        let request = web::Request::new_with_str_and_init(&self.url, &init).unwrap();
-        let promise = web::window().unwrap().fetch_with_request(&request);
-        R::send(JsFuture::from(promise).map(|response| {
-            assert!(response.is_instance_of::<web::Response>());
-            response.dyn_into::<web::Response>().unwrap()
-        }))
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| &**value)
+    }
+}
+
+pub struct Pending<T> {
+    future: Box<Future<Item = T, Error = Error>>,
+    abort: Option<Rc<Fn()>>,
+}
+
+impl<T> Pending<T> {
+    pub fn new(future: impl Future<Item = T, Error = Error> + 'static) -> Self {
+        Pending {
+            future: Box::new(future),
+            abort: None,
+        }
+    }
+
+    pub fn on_abort(mut self, f: impl Fn() + 'static) -> Self {
+        self.abort = Some(Rc::new(f));
+        self
+    }
+
+    // Returns a handle which aborts the request when dropped. The request then fails with
+    // `Error::Aborted`. Every handle does this, so dropping any one of them aborts the request,
+    // and so does not binding the returned handle at all.
+    #[must_use = "dropping the handle aborts the request"]
+    pub fn abort_handle(&self) -> Abort {
+        Abort(self.abort.clone())
+    }
+}
+
+impl<T> Future for Pending<T> {
+    type Item = T;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<T, Error> {
+        self.future.poll()
+    }
+}
+
+impl<T> std::fmt::Debug for Pending<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Pending").finish()
+    }
+}
+
+#[must_use = "dropping the handle aborts the request"]
+pub struct Abort(Option<Rc<Fn()>>);
+
+impl Abort {
+    pub fn abort(self) {}
+}
+
+impl Drop for Abort {
+    fn drop(&mut self) {
+        if let Some(abort) = self.0.take() {
+            abort();
+        }
+    }
+}
+
+impl std::fmt::Debug for Abort {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Abort").finish()
     }
 }
 
 #[derive(Debug)]
-pub struct Error(JsValue);
+pub enum Error {
+    // The request couldn't be built, e.g. because of an invalid URL or header name, so it was
+    // never sent.
+    Request(JsValue),
+    // The browser reports CORS rejections as plain network errors, so they end up here as well.
+    Network(JsValue),
+    Status { code: u16, body: String },
+    Decode(String),
+    Aborted,
+    Timeout,
+    // Every failed attempt of a request sent with a `Retry` policy, oldest first.
+    Retries(Vec<Error>),
+}
+
+impl Error {
+    pub fn is_network(&self) -> bool {
+        match self {
+            Error::Network(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::Status { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+impl From<JsValue> for Error {
+    fn from(value: JsValue) -> Self {
+        let name = js::Reflect::get(&value, &JsValue::from_str("name"))
+            .ok()
+            .and_then(|name| name.as_string());
+        if name.as_ref().map_or(false, |name| name == "AbortError") {
+            Error::Aborted
+        } else {
+            Error::Network(value)
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Request(value) => match message(value) {
+                Some(message) => write!(f, "invalid request: {}", message),
+                None => write!(f, "invalid request: {:?}", value),
+            },
+            Error::Network(value) => match message(value) {
+                Some(message) => write!(f, "network error: {}", message),
+                None => write!(f, "network error: {:?}", value),
+            },
+            Error::Status { code, .. } => write!(f, "request failed with status {}", code),
+            Error::Decode(message) => write!(f, "failed to decode response: {}", message),
+            Error::Aborted => write!(f, "request aborted"),
+            Error::Timeout => write!(f, "request timed out"),
+            Error::Retries(errors) => {
+                write!(f, "request failed after {} attempts", errors.len())?;
+                if let Some(error) = errors.last() {
+                    write!(f, ": {}", error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+fn message(value: &JsValue) -> Option<String> {
+    value.as_string().or_else(|| {
+        js::Reflect::get(value, &JsValue::from_str("message"))
+            .ok()
+            .and_then(|message| message.as_string())
+    })
+}
 
-pub trait Response {
-    type Item;
+pub trait Response: 'static {
+    type Item: 'static;
 
-    fn send(
-        future: impl Future<Item = web::Response, Error = JsValue> + 'static,
-    ) -> Box<Future<Item = Self::Item, Error = Error>>;
+    fn decode(response: RawResponse) -> Result<Self::Item, Error>;
+
+    fn receive(response: RawResponse) -> Result<Self::Item, Error> {
+        if !response.is_ok() {
+            return Err(Error::Status {
+                code: response.status,
+                body: String::from_utf8_lossy(&response.body).into_owned(),
+            });
+        }
+        Self::decode(response)
+    }
 }
 
 #[derive(Debug)]
@@ -46,19 +532,132 @@ pub struct Text;
 impl Response for Text {
     type Item = String;
 
-    fn send(
-        future: impl Future<Item = web::Response, Error = JsValue> + 'static,
-    ) -> Box<Future<Item = Self::Item, Error = Error>> {
-        Box::new(
-            future
-                .and_then(|response| response.text())
-                .and_then(JsFuture::from)
-                .map(|text| text.as_string().unwrap())
-                .map_err(Error),
-        )
+    fn decode(response: RawResponse) -> Result<Self::Item, Error> {
+        String::from_utf8(response.body).map_err(|error| Error::Decode(error.to_string()))
+    }
+}
+
+#[derive(Debug)]
+pub struct Bytes;
+
+impl Response for Bytes {
+    type Item = Vec<u8>;
+
+    fn decode(response: RawResponse) -> Result<Self::Item, Error> {
+        Ok(response.body)
+    }
+}
+
+#[derive(Debug)]
+pub struct Blob;
+
+impl Response for Blob {
+    type Item = web::Blob;
+
+    fn decode(response: RawResponse) -> Result<Self::Item, Error> {
+        let parts = js::Array::new();
+        parts.push(&uint8_array(&response.body));
+        let mut options = web::BlobPropertyBag::new();
+        if let Some(content_type) = response.get_header("content-type") {
+            options.type_(content_type);
+        }
+        web::Blob::new_with_u8_array_sequence_and_options(&parts, &options).map_err(Error::from)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub struct Json<T>(std::marker::PhantomData<T>);
+
+#[cfg(feature = "serde")]
+impl<T: serde::de::DeserializeOwned + 'static> Response for Json<T> {
+    type Item = T;
+
+    fn decode(response: RawResponse) -> Result<Self::Item, Error> {
+        serde_json::from_slice(&response.body).map_err(|error| Error::Decode(error.to_string()))
+    }
+}
+
+// Unlike the other responses, `Full` doesn't treat non-2xx statuses as errors, since the status is
+// returned to the caller anyway. If a non-2xx body doesn't decode, e.g. an HTML error page for a
+// `Full<Json<T>>`, it fails with `Error::Status` instead of `Error::Decode`.
+#[derive(Debug)]
+pub struct Full<T> {
+    pub status: u16,
+    pub status_text: String,
+    pub headers: Vec<(String, String)>,
+    pub body: T,
+}
+
+impl<R: Response> Response for Full<R> {
+    type Item = Full<R::Item>;
+
+    fn decode(response: RawResponse) -> Result<Self::Item, Error> {
+        let status = response.status;
+        let status_text = response.status_text.clone();
+        let headers = response.headers.clone();
+        Ok(Full {
+            status,
+            status_text,
+            headers,
+            body: R::decode(response)?,
+        })
+    }
+
+    fn receive(response: RawResponse) -> Result<Self::Item, Error> {
+        if response.is_ok() {
+            return Self::decode(response);
+        }
+        let code = response.status;
+        let body = String::from_utf8_lossy(&response.body).into_owned();
+        Self::decode(response).map_err(|_| Error::Status { code, body })
     }
 }
 
 pub fn get(url: &str) -> Request {
     Request::new("GET", url)
 }
+
+pub fn post(url: &str) -> Request {
+    Request::new("POST", url)
+}
+
+pub fn put(url: &str) -> Request {
+    Request::new("PUT", url)
+}
+
+pub fn patch(url: &str) -> Request {
+    Request::new("PATCH", url)
+}
+
+pub fn delete(url: &str) -> Request {
+    Request::new("DELETE", url)
+}
+
+pub fn head(url: &str) -> Request {
+    Request::new("HEAD", url)
+}
+
+// Percent-encodes everything except the characters `encodeURIComponent` leaves alone. Unlike
+// `encodeURIComponent`, this also works outside the browser, e.g. with a `Mock` transport.
+pub(crate) fn encode(str: &str) -> String {
+    let mut encoded = String::with_capacity(str.len());
+    for byte in str.bytes() {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'!'
+            | b'~'
+            | b'*'
+            | b'\''
+            | b'('
+            | b')' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
@@ -0,0 +1,145 @@
+use super::{Error, Pending, RawResponse, Request, Transport};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Clone, Debug, Default)]
+pub struct Mock {
+    inner: Rc<RefCell<Inner>>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    replies: Vec<(String, String, Reply)>,
+    requests: Vec<Request>,
+}
+
+#[derive(Debug)]
+enum Reply {
+    Always(RawResponse),
+    Once(Option<Result<RawResponse, Error>>),
+}
+
+impl Mock {
+    pub fn new() -> Self {
+        Mock::default()
+    }
+
+    pub fn respond(&self, method: &str, url: &str, response: RawResponse) -> &Self {
+        self.inner
+            .borrow_mut()
+            .replies
+            .push((method.into(), url.into(), Reply::Always(response)));
+        self
+    }
+
+    // One-off replies are used up in the order they were added and take precedence over replies
+    // added with `respond`.
+    pub fn respond_once(
+        &self,
+        method: &str,
+        url: &str,
+        result: Result<RawResponse, Error>,
+    ) -> &Self {
+        self.inner.borrow_mut().replies.push((
+            method.into(),
+            url.into(),
+            Reply::Once(Some(result)),
+        ));
+        self
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.inner.borrow().requests.clone()
+    }
+
+    fn reply(&self, method: &str, url: &str) -> Result<RawResponse, Error> {
+        let mut inner = self.inner.borrow_mut();
+        let mut matching = inner
+            .replies
+            .iter_mut()
+            .filter(|(m, u, _)| m == method && u == url)
+            .map(|(_, _, reply)| reply)
+            .collect::<Vec<_>>();
+        for reply in &mut matching {
+            if let Reply::Once(result) = reply {
+                if let Some(result) = result.take() {
+                    return result;
+                }
+            }
+        }
+        for reply in matching.iter().rev() {
+            if let Reply::Always(response) = reply {
+                return Ok(response.clone());
+            }
+        }
+        Err(Error::Status {
+            code: 404,
+            body: format!("no mock response for {} {}", method, url),
+        })
+    }
+}
+
+impl Transport for Mock {
+    fn send(&self, request: Request) -> Pending<RawResponse> {
+        let result = self.reply(request.method(), &request.url());
+        self.inner.borrow_mut().requests.push(request);
+        Pending::new(futures::future::result(result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fetch::{self, Error, Full, Mock, RawResponse, Text};
+    use futures::Future;
+
+    #[test]
+    fn mock() {
+        let mock = Mock::new();
+        mock.respond("GET", "/posts?q=a%20b", RawResponse::new(200).body("posts"))
+            .respond_once("GET", "/posts?q=a%20b", Err(Error::Timeout))
+            .respond("POST", "/posts", RawResponse::new(500).body("oops"));
+        fetch::set_transport(mock.clone());
+
+        let get = || fetch::get("/posts").query("q", "a b").send::<Text>().wait();
+        match get() {
+            Err(Error::Timeout) => {}
+            other => panic!("{:?}", other.map_err(|error| error.to_string())),
+        }
+        assert_eq!(get().ok(), Some("posts".to_string()));
+
+        match fetch::post("/posts").body_text("{}").send::<Text>().wait() {
+            Err(Error::Status { code, body }) => assert_eq!((code, &*body), (500, "oops")),
+            other => panic!("{:?}", other.map_err(|error| error.to_string())),
+        }
+        let full = fetch::post("/posts")
+            .send::<Full<Text>>()
+            .wait()
+            .ok()
+            .unwrap();
+        assert_eq!((full.status, &*full.body), (500, "oops"));
+
+        assert_eq!(
+            fetch::get("/nope")
+                .send::<Text>()
+                .wait()
+                .err()
+                .unwrap()
+                .status(),
+            Some(404)
+        );
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 5);
+        assert_eq!(requests[2].method(), "POST");
+        match requests[2].body() {
+            Some(fetch::Body::Text(text)) => assert_eq!(text, "{}"),
+            _ => panic!(),
+        }
+
+        mock.respond("GET", "/error", RawResponse::new(502).body(vec![0xff]));
+        match fetch::get("/error").send::<Full<Text>>().wait() {
+            Err(Error::Status { code, .. }) => assert_eq!(code, 502),
+            other => panic!("{:?}", other.map_err(|error| error.to_string())),
+        }
+    }
+}
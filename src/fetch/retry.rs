@@ -0,0 +1,209 @@
+use super::{Error, Pending, Request, Response, Transport};
+use crate::timer;
+use futures::future::{self, Either, Loop};
+use futures::Future;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+#[derive(Clone)]
+pub struct Retry {
+    max_attempts: usize,
+    delay: i32,
+    max_delay: i32,
+    factor: f64,
+    jitter: bool,
+    network: bool,
+    status: Rc<Fn(u16) -> bool>,
+}
+
+impl Retry {
+    // By default, network errors, timeouts and 408, 429 and 5xx responses are retried, starting
+    // after 100ms and doubling the delay after every attempt, up to 10s.
+    pub fn new(max_attempts: usize) -> Self {
+        Retry {
+            max_attempts,
+            delay: 100,
+            max_delay: 10_000,
+            factor: 2.0,
+            jitter: true,
+            network: true,
+            status: Rc::new(|status| status == 408 || status == 429 || status >= 500),
+        }
+    }
+
+    pub fn delay(mut self, ms: i32) -> Self {
+        self.delay = ms;
+        self
+    }
+
+    pub fn max_delay(mut self, ms: i32) -> Self {
+        self.max_delay = ms;
+        self
+    }
+
+    pub fn factor(mut self, factor: f64) -> Self {
+        self.factor = factor;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn on_network_error(mut self, retry: bool) -> Self {
+        self.network = retry;
+        self
+    }
+
+    pub fn on_status(mut self, f: impl Fn(u16) -> bool + 'static) -> Self {
+        self.status = Rc::new(f);
+        self
+    }
+
+    fn should_retry(&self, error: &Error) -> bool {
+        match error {
+            Error::Network(_) | Error::Timeout => self.network,
+            Error::Status { code, .. } => (self.status)(*code),
+            _ => false,
+        }
+    }
+
+    // `random` is in `[0, 1)`. With jitter, the delay is picked uniformly from the upper half of
+    // the exponential backoff.
+    fn backoff(&self, attempt: usize, random: f64) -> i32 {
+        let delay = f64::from(self.delay) * self.factor.powi(attempt as i32 - 1);
+        let delay = delay.min(f64::from(self.max_delay));
+        let delay = if self.jitter {
+            delay * (0.5 + random / 2.0)
+        } else {
+            delay
+        };
+        delay as i32
+    }
+}
+
+impl std::fmt::Debug for Retry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Retry")
+            .field("max_attempts", &self.max_attempts)
+            .field("delay", &self.delay)
+            .field("max_delay", &self.max_delay)
+            .field("factor", &self.factor)
+            .field("jitter", &self.jitter)
+            .field("network", &self.network)
+            .finish()
+    }
+}
+
+#[derive(Default)]
+struct State {
+    aborted: Cell<bool>,
+    abort: RefCell<Option<Rc<Fn()>>>,
+}
+
+pub(crate) fn send<R: Response>(
+    request: Request,
+    transport: Rc<Transport>,
+    retry: Retry,
+) -> Pending<R::Item> {
+    let state = Rc::new(State::default());
+    let future = future::loop_fn(Vec::new(), {
+        let state = state.clone();
+        move |mut errors: Vec<Error>| {
+            if state.aborted.get() {
+                return Either::A(future::err(Error::Aborted));
+            }
+            let Pending { future, abort } = transport.send(request.clone());
+            state.abort.replace(abort);
+            let state = state.clone();
+            let retry = retry.clone();
+            Either::B(future.and_then(R::receive).then(move |result| {
+                let error = match result {
+                    Ok(item) => return Either::A(future::ok(Loop::Break(item))),
+                    Err(error) => error,
+                };
+                if state.aborted.get() {
+                    return Either::A(future::err(Error::Aborted));
+                }
+                let retryable = retry.should_retry(&error);
+                errors.push(error);
+                if !retryable || errors.len() >= retry.max_attempts {
+                    let error = if errors.len() == 1 {
+                        errors.pop().unwrap()
+                    } else {
+                        Error::Retries(errors)
+                    };
+                    return Either::A(future::err(error));
+                }
+                let delay = retry.backoff(errors.len(), random());
+                Either::B(timer::delay(delay).then(move |_| Ok(Loop::Continue(errors))))
+            }))
+        }
+    });
+    Pending::new(future).on_abort(move || {
+        state.aborted.set(true);
+        let abort = state.abort.borrow().clone();
+        if let Some(abort) = abort {
+            abort();
+        }
+    })
+}
+
+#[cfg(target_arch = "wasm32")]
+fn random() -> f64 {
+    js_sys::Math::random()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn random() -> f64 {
+    0.5
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fetch::{self, Error, Mock, RawResponse, Retry, Text};
+    use futures::Future;
+
+    #[test]
+    fn retry() {
+        let mock = Mock::new();
+        mock.respond_once("GET", "/flaky", Ok(RawResponse::new(503)))
+            .respond_once("GET", "/flaky", Ok(RawResponse::new(502)))
+            .respond("GET", "/flaky", RawResponse::new(200).body("ok"))
+            .respond("GET", "/missing", RawResponse::new(404));
+        fetch::set_transport(mock.clone());
+
+        let flaky = |attempts| {
+            fetch::get("/flaky")
+                .retry(Retry::new(attempts))
+                .send::<Text>()
+                .wait()
+        };
+        match flaky(2) {
+            Err(Error::Retries(errors)) => assert_eq!(
+                errors.iter().map(Error::status).collect::<Vec<_>>(),
+                [Some(503), Some(502)]
+            ),
+            other => panic!("{:?}", other.map_err(|error| error.to_string())),
+        }
+        mock.respond_once("GET", "/flaky", Ok(RawResponse::new(503)));
+        assert_eq!(flaky(3).ok(), Some("ok".to_string()));
+
+        let missing = fetch::get("/missing").retry(Retry::new(5)).send::<Text>();
+        assert_eq!(
+            missing.wait().err().and_then(|error| error.status()),
+            Some(404)
+        );
+        assert_eq!(mock.requests().len(), 5);
+    }
+
+    #[test]
+    fn backoff() {
+        let retry = Retry::new(10).jitter(false);
+        let delays = (1..6).map(|n| retry.backoff(n, 0.0)).collect::<Vec<_>>();
+        assert_eq!(delays, [100, 200, 400, 800, 1600]);
+        assert_eq!(retry.max_delay(500).backoff(5, 0.0), 500);
+        assert_eq!(Retry::new(10).backoff(2, 0.0), 100);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::fetch;
+use crate::subscription::{self, Subscription, Unsubscribe};
+use futures::{Future, Poll};
+use js_sys as js;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys as web;
+
+pub(crate) fn files(list: Option<web::FileList>) -> Vec<web::File> {
+    let mut files = Vec::new();
+    if let Some(list) = list {
+        for index in 0..list.length() {
+            if let Some(file) = list.get(index) {
+                files.push(file);
+            }
+        }
+    }
+    files
+}
+
+pub fn read_as_text(blob: &web::Blob) -> impl Future<Item = String, Error = JsValue> {
+    read(blob, |reader, blob| reader.read_as_text(blob))
+        .map(|result| result.as_string().unwrap_or_default())
+}
+
+pub fn read_as_bytes(blob: &web::Blob) -> impl Future<Item = Vec<u8>, Error = JsValue> {
+    read(blob, |reader, blob| reader.read_as_array_buffer(blob))
+        .map(|result| fetch::bytes(&js::Uint8Array::new(&result)))
+}
+
+pub fn read_as_data_url(blob: &web::Blob) -> impl Future<Item = String, Error = JsValue> {
+    read(blob, |reader, blob| reader.read_as_data_url(blob))
+        .map(|result| result.as_string().unwrap_or_default())
+}
+
+fn read(
+    blob: &web::Blob,
+    start: impl FnOnce(&web::FileReader, &web::Blob) -> Result<(), JsValue>,
+) -> Box<Future<Item = JsValue, Error = JsValue>> {
+    let reader = match web::FileReader::new() {
+        Ok(reader) => reader,
+        Err(error) => return Box::new(futures::future::err(error)),
+    };
+    let mut closures = None;
+    let promise = js::Promise::new(&mut |resolve, reject| {
+        let cloned = reader.clone();
+        let onload = Closure::wrap(Box::new(move |_: web::Event| {
+            let result = cloned.result().unwrap_or(JsValue::UNDEFINED);
+            resolve.call1(&JsValue::NULL, &result).unwrap();
+        }) as Box<FnMut(web::Event)>);
+        let onerror = Closure::wrap(Box::new(move |event: web::Event| {
+            reject.call1(&JsValue::NULL, &event).unwrap();
+        }) as Box<FnMut(web::Event)>);
+        reader.set_onload(Some(onload.as_ref().unchecked_ref()));
+        reader.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+        closures = Some((onload, onerror));
+    });
+    let read = Read {
+        reader,
+        future: JsFuture::from(promise),
+        _closures: closures.expect("closures"),
+    };
+    if let Err(error) = start(&read.reader, blob) {
+        return Box::new(futures::future::err(error));
+    }
+    Box::new(read)
+}
+
+// Owns the reader's closures, and clears them and aborts the reader when dropped, so that a
+// dropped read never calls a freed closure.
+struct Read {
+    reader: web::FileReader,
+    future: JsFuture,
+    _closures: (Closure<FnMut(web::Event)>, Closure<FnMut(web::Event)>),
+}
+
+impl Future for Read {
+    type Item = JsValue;
+    type Error = JsValue;
+
+    fn poll(&mut self) -> Poll<JsValue, JsValue> {
+        self.future.poll()
+    }
+}
+
+impl Drop for Read {
+    fn drop(&mut self) {
+        self.reader.set_onload(None);
+        self.reader.set_onerror(None);
+        self.reader.abort();
+    }
+}
+
+#[derive(Debug)]
+pub enum Body {
+    Blob(web::Blob),
+    FormData(web::FormData),
+}
+
+impl From<web::Blob> for Body {
+    fn from(blob: web::Blob) -> Self {
+        Body::Blob(blob)
+    }
+}
+
+impl From<web::File> for Body {
+    fn from(file: web::File) -> Self {
+        Body::Blob(file.into())
+    }
+}
+
+impl From<web::FormData> for Body {
+    fn from(form_data: web::FormData) -> Self {
+        Body::FormData(form_data)
+    }
+}
+
+#[derive(Debug)]
+pub enum Progress {
+    Uploading { loaded: f64, total: Option<f64> },
+    Done { status: u16, body: String },
+    Failed,
+}
+
+// `fetch` doesn't report upload progress, so uploads go through `XMLHttpRequest` instead.
+#[derive(Debug)]
+pub struct Upload {
+    method: String,
+    url: String,
+    body: Body,
+}
+
+impl Upload {
+    pub fn new<B: Into<Body>>(method: &str, url: &str, body: B) -> Self {
+        Upload {
+            method: method.into(),
+            url: url.into(),
+            body: body.into(),
+        }
+    }
+}
+
+impl Subscription for Upload {
+    type Message = Progress;
+
+    fn subscribe(self, send: subscription::Send<Self::Message>) -> Unsubscribe {
+        let xhr = web::XmlHttpRequest::new().unwrap();
+        let onprogress = Closure::wrap(Box::new({
+            let send = send.clone();
+            move |event: web::ProgressEvent| {
+                send(Progress::Uploading {
+                    loaded: event.loaded(),
+                    total: if event.length_computable() {
+                        Some(event.total())
+                    } else {
+                        None
+                    },
+                })
+            }
+        }) as Box<FnMut(web::ProgressEvent)>);
+        let onload = Closure::wrap(Box::new({
+            let send = send.clone();
+            let xhr = xhr.clone();
+            move |_: web::Event| {
+                send(Progress::Done {
+                    status: xhr.status().unwrap_or(0),
+                    body: xhr
+                        .response_text()
+                        .ok()
+                        .and_then(|text| text)
+                        .unwrap_or_default(),
+                })
+            }
+        }) as Box<FnMut(web::Event)>);
+        let onerror = Closure::wrap(Box::new({
+            let send = send.clone();
+            move |_: web::Event| send(Progress::Failed)
+        }) as Box<FnMut(web::Event)>);
+        xhr.upload()
+            .unwrap()
+            .set_onprogress(Some(onprogress.as_ref().unchecked_ref()));
+        xhr.set_onload(Some(onload.as_ref().unchecked_ref()));
+        xhr.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+        // `open` throws for an invalid method or URL.
+        let sent = xhr
+            .open(&self.method, &self.url)
+            .and_then(|()| match &self.body {
+                Body::Blob(blob) => xhr.send_with_opt_blob(Some(blob)),
+                Body::FormData(form_data) => xhr.send_with_opt_form_data(Some(form_data)),
+            });
+        if sent.is_err() {
+            send(Progress::Failed);
+        }
+        Unsubscribe::new(move || {
+            // We need to move the closures here so that they aren't dropped too early.
+            let _ = (&onprogress, &onload, &onerror);
+            xhr.upload().unwrap().set_onprogress(None);
+            xhr.set_onload(None);
+            xhr.set_onerror(None);
+            xhr.abort().unwrap();
+        })
+    }
+}
@@ -0,0 +1,330 @@
+use crate::{fetch, html, NonKeyedElement};
+use std::rc::Rc;
+use std::str::FromStr;
+use wasm_bindgen::JsValue;
+use web_sys as web;
+
+#[derive(Clone)]
+pub struct Validator {
+    func: Rc<Fn(&str) -> Result<(), String>>,
+}
+
+impl Validator {
+    pub fn new(func: impl Fn(&str) -> Result<(), String> + 'static) -> Self {
+        Validator {
+            func: Rc::new(func),
+        }
+    }
+
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        (self.func)(value)
+    }
+
+    pub fn message<M: Into<String>>(self, message: M) -> Self {
+        let message = message.into();
+        Validator::new(move |value| self.validate(value).map_err(|_| message.clone()))
+    }
+
+    pub fn and(self, other: Validator) -> Self {
+        Validator::new(move |value| {
+            self.validate(value)?;
+            other.validate(value)
+        })
+    }
+}
+
+impl std::fmt::Debug for Validator {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Validator").finish()
+    }
+}
+
+pub fn required() -> Validator {
+    Validator::new(|value| {
+        if value.trim().is_empty() {
+            Err("is required".into())
+        } else {
+            Ok(())
+        }
+    })
+}
+
+pub fn min_length(min: usize) -> Validator {
+    Validator::new(move |value| {
+        if value.chars().count() < min {
+            Err(format!("must be at least {} characters", min))
+        } else {
+            Ok(())
+        }
+    })
+}
+
+pub fn max_length(max: usize) -> Validator {
+    Validator::new(move |value| {
+        if value.chars().count() > max {
+            Err(format!("must be at most {} characters", max))
+        } else {
+            Ok(())
+        }
+    })
+}
+
+// Uses the browser's `RegExp`, so this only works in the browser. An invalid `pattern` rejects
+// every value.
+pub fn regex(pattern: &str) -> Validator {
+    let regexp = crate::regexp::new(pattern, "u");
+    Validator::new(move |value| match &regexp {
+        Some(regexp) if regexp.test(value) => Ok(()),
+        _ => Err("is invalid".into()),
+    })
+}
+
+pub fn custom(func: impl Fn(&str) -> Result<(), String> + 'static) -> Validator {
+    Validator::new(func)
+}
+
+#[derive(Debug)]
+pub enum Message {
+    Input(String),
+    Blur,
+}
+
+#[derive(Clone, Debug)]
+pub struct Field {
+    name: String,
+    value: String,
+    initial: String,
+    touched: bool,
+    errors: Vec<String>,
+    validators: Vec<Validator>,
+}
+
+impl Field {
+    pub fn new<N: Into<String>>(name: N) -> Self {
+        Field {
+            name: name.into(),
+            value: String::new(),
+            initial: String::new(),
+            touched: false,
+            errors: Vec::new(),
+            validators: Vec::new(),
+        }
+    }
+
+    pub fn initial<V: Into<String>>(mut self, value: V) -> Self {
+        self.value = value.into();
+        self.initial = self.value.clone();
+        self.run_validators();
+        self
+    }
+
+    pub fn validate(mut self, validator: Validator) -> Self {
+        self.validators.push(validator);
+        self.run_validators();
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn parse<T: FromStr>(&self) -> Result<T, T::Err> {
+        self.value.parse()
+    }
+
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn is_touched(&self) -> bool {
+        self.touched
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.value != self.initial
+    }
+
+    // Errors are usually only shown once the user has interacted with the field.
+    pub fn visible_errors(&self) -> &[String] {
+        if self.touched {
+            &self.errors
+        } else {
+            &[]
+        }
+    }
+
+    pub fn set<V: Into<String>>(&mut self, value: V) {
+        self.value = value.into();
+        self.run_validators();
+    }
+
+    pub fn touch(&mut self) {
+        self.touched = true;
+    }
+
+    pub fn reset(&mut self) {
+        self.value = self.initial.clone();
+        self.touched = false;
+        self.run_validators();
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Input(value) => self.set(value),
+            Message::Blur => self.touch(),
+        }
+    }
+
+    pub fn bind<AppMessage: 'static>(
+        &self,
+        element: NonKeyedElement<AppMessage>,
+        f: impl Fn(Message) -> AppMessage + 'static,
+    ) -> NonKeyedElement<AppMessage> {
+        let f = Rc::new(f);
+        let element = element
+            .attr("name", self.name.clone())
+            .attr("value", self.value.clone())
+            .on_input({
+                let f = f.clone();
+                move |value| f(Message::Input(value))
+            })
+            .on("blur", move |_| f(Message::Blur));
+        if self.touched && !self.is_valid() {
+            element.class("invalid")
+        } else {
+            element
+        }
+    }
+
+    pub fn input<AppMessage: 'static>(
+        &self,
+        f: impl Fn(Message) -> AppMessage + 'static,
+    ) -> NonKeyedElement<AppMessage> {
+        self.bind(html::input(), f)
+    }
+
+    pub fn textarea<AppMessage: 'static>(
+        &self,
+        f: impl Fn(Message) -> AppMessage + 'static,
+    ) -> NonKeyedElement<AppMessage> {
+        self.bind(html::textarea(), f)
+    }
+
+    fn run_validators(&mut self) {
+        let value = &self.value;
+        self.errors = self
+            .validators
+            .iter()
+            .filter_map(|validator| validator.validate(value).err())
+            .collect();
+    }
+}
+
+pub fn is_valid(fields: &[&Field]) -> bool {
+    fields.iter().all(|field| field.is_valid())
+}
+
+pub fn to_query_string(fields: &[&Field]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            format!(
+                "{}={}",
+                fetch::encode(&field.name),
+                fetch::encode(&field.value)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+pub fn to_form_data(fields: &[&Field]) -> Result<web::FormData, JsValue> {
+    let form_data = web::FormData::new()?;
+    for field in fields {
+        form_data.append_with_str(&field.name, &field.value)?;
+    }
+    Ok(form_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        custom, is_valid, max_length, min_length, required, to_query_string, Field, Message,
+    };
+
+    #[test]
+    fn validators() {
+        assert_eq!(required().validate(" "), Err("is required".into()));
+        assert_eq!(required().validate("a"), Ok(()));
+        assert!(min_length(2).validate("é").is_err());
+        assert!(min_length(2).validate("éé").is_ok());
+        assert!(max_length(2).validate("ééé").is_err());
+        assert!(max_length(2).validate("éé").is_ok());
+
+        let even = custom(|value| match value.parse::<i32>() {
+            Ok(n) if n % 2 == 0 => Ok(()),
+            _ => Err("must be even".into()),
+        });
+        assert_eq!(even.validate("3"), Err("must be even".into()));
+        assert_eq!(even.validate("4"), Ok(()));
+
+        let name = required().message("enter a name").and(max_length(3));
+        assert_eq!(name.validate(""), Err("enter a name".into()));
+        assert_eq!(
+            name.validate("abcd"),
+            Err("must be at most 3 characters".into())
+        );
+        assert_eq!(name.validate("abc"), Ok(()));
+    }
+
+    #[test]
+    fn field() {
+        let mut field = Field::new("name")
+            .initial("ab")
+            .validate(required())
+            .validate(min_length(3));
+        assert_eq!(
+            field.errors(),
+            &["must be at least 3 characters".to_string()]
+        );
+        assert!(!field.is_dirty() && !field.is_touched());
+        assert!(field.visible_errors().is_empty());
+
+        field.update(Message::Input("".into()));
+        assert!(field.is_dirty());
+        assert_eq!(field.errors().len(), 2);
+        field.update(Message::Blur);
+        assert!(field.is_touched());
+        assert_eq!(field.visible_errors().len(), 2);
+
+        field.set("abc");
+        assert!(field.is_valid());
+        assert!(is_valid(&[&field]));
+
+        field.reset();
+        assert_eq!(field.value(), "ab");
+        assert!(!field.is_dirty() && !field.is_touched() && !field.is_valid());
+        assert!(!is_valid(&[&field]));
+
+        assert_eq!(Field::new("n").initial("12").parse::<u8>(), Ok(12));
+    }
+
+    #[test]
+    fn query_string() {
+        let name = Field::new("full name").initial("A & B");
+        let age = Field::new("age").initial("30");
+        assert_eq!(
+            to_query_string(&[&name, &age]),
+            "full%20name=A%20%26%20B&age=30"
+        );
+        assert_eq!(to_query_string(&[]), "");
+    }
+}
@@ -1,15 +1,22 @@
+#[cfg(feature = "serde")]
+extern crate serde_crate as serde;
+
 #[macro_use]
 pub mod console;
 pub mod app;
 pub mod element;
 pub mod fetch;
+pub mod file;
+pub mod form;
 pub mod html;
 pub mod mailbox;
 pub mod node;
+mod regexp;
 pub mod router;
 pub mod subscription;
 pub mod svg;
 pub mod text;
+mod timer;
 
 pub use self::app::{start, App, Instance};
 pub use self::element::{h, s};
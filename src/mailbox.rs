@@ -1,10 +1,16 @@
+use crate::timer;
 use crate::{Subscription, Unsubscribe};
 use futures::Future;
+#[cfg(not(target_arch = "wasm32"))]
+use futures::{executor, Async};
+#[cfg(not(target_arch = "wasm32"))]
+use std::cell::RefCell;
 use std::rc::Rc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
 use wasm_bindgen::prelude::*;
-use wasm_bindgen::JsCast;
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen_futures::future_to_promise;
-use web_sys as web;
 
 pub struct Mailbox<Message: 'static> {
     func: Rc<Fn(Message)>,
@@ -21,20 +27,13 @@ impl<Message: 'static> Mailbox<Message> {
         (self.func)(message)
     }
 
+    // Use `subscription::Timeout` instead if this may need to be cancelled.
     pub fn send_after(&self, timeout: i32, f: impl Fn() -> Message + 'static) {
         let cloned = self.clone();
-        let closure = Closure::wrap(Box::new(move || {
+        spawn(timer::delay(timeout).then(move |_| {
             cloned.send(f());
-        }) as Box<FnMut()>);
-        web::window()
-            .unwrap()
-            .set_timeout_with_callback_and_timeout_and_arguments_0(
-                closure.as_ref().unchecked_ref(),
-                timeout,
-            )
-            .unwrap();
-        // TODO: Stash this closure in the Mailbox and drop it when the closure is first called.
-        closure.forget();
+            futures::future::ok(JsValue::UNDEFINED)
+        }));
     }
 
     pub fn subscribe<S: Subscription + 'static>(
@@ -64,7 +63,52 @@ impl<Message: 'static> Mailbox<Message> {
             cloned.send(func(result));
             futures::future::ok(wasm_bindgen::JsValue::UNDEFINED)
         });
-        future_to_promise(future);
+        spawn(future);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn spawn(future: impl Future<Item = JsValue, Error = JsValue> + 'static) {
+    future_to_promise(future);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type Spawned = executor::Spawn<Box<Future<Item = JsValue, Error = JsValue>>>;
+
+#[cfg(not(target_arch = "wasm32"))]
+thread_local! {
+    static SPAWNED: RefCell<Vec<Spawned>> = RefCell::new(Vec::new());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct Noop;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl executor::Notify for Noop {
+    fn notify(&self, _id: usize) {}
+}
+
+// Outside the browser (e.g. in `cargo test` with a mock `fetch::Transport`) there's no event loop
+// to hand the future to, so it's polled right away. Futures which aren't ready yet are kept and
+// polled again by `run`.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn spawn(future: impl Future<Item = JsValue, Error = JsValue> + 'static) {
+    let spawned: Box<Future<Item = JsValue, Error = JsValue>> = Box::new(future);
+    poll(vec![executor::spawn(spawned)]);
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+pub(crate) fn run() {
+    poll(SPAWNED.with(|spawned| spawned.replace(Vec::new())));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn poll(spawned: Vec<Spawned>) {
+    let notify = Arc::new(Noop);
+    for mut future in spawned {
+        if let Ok(Async::NotReady) = future.poll_future_notify(&notify, 0) {
+            SPAWNED.with(|spawned| spawned.borrow_mut().push(future));
+        }
     }
 }
 
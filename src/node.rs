@@ -53,6 +53,14 @@ impl<Message: 'static> Node<Message> {
         }
     }
 
+    pub(crate) fn detach(&mut self) {
+        match self {
+            Node::Element(element) => element.detach(),
+            Node::KeyedElement(keyed_element) => keyed_element.detach(),
+            Node::Text(_) => {}
+        }
+    }
+
     pub fn map<NewMessage: 'static>(
         self,
         f: impl Fn(Message) -> NewMessage + 'static,
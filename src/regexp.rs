@@ -0,0 +1,14 @@
+use js_sys as js;
+use wasm_bindgen::JsCast;
+
+// `RegExp::new` throws for an invalid pattern, so the constructor is called through `Reflect`,
+// which returns the exception instead.
+pub(crate) fn new(pattern: &str, flags: &str) -> Option<js::RegExp> {
+    let constructor = js::Reflect::get(&js::global(), &"RegExp".into()).ok()?;
+    let arguments = js::Array::new();
+    arguments.push(&pattern.into());
+    arguments.push(&flags.into());
+    js::Reflect::construct(constructor.unchecked_ref::<js::Function>(), &arguments)
+        .ok()
+        .map(JsCast::unchecked_into)
+}
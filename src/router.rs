@@ -1,28 +1,164 @@
-use crate::{subscription, NonKeyedElement, Subscription, Unsubscribe};
+use crate::subscription::{self, window::Position, OnDocument, Scroll};
+use crate::{Subscription, Unsubscribe};
+use js_sys as js;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use web_sys as web;
 
+mod guard;
+mod link;
+mod load;
 mod parse;
+mod route;
 mod url;
 
-pub use self::parse::{hash, param, parse, query, Parse};
+pub use self::guard::{before_navigate, block, Guard, Navigate, Navigation};
+pub use self::link::{is_active, link, nav_link};
+pub use self::load::{Load, Loaded, Loader, Status};
+#[cfg(target_arch = "wasm32")]
+pub use self::parse::regex;
+pub use self::parse::{end, hash, nest, param, parse, query, query_all, query_opt, rest};
+pub use self::parse::{segment, Parse, State};
+pub use self::route::{nested, Route};
 pub use self::url::Url;
+#[cfg(feature = "derive")]
+pub use draco_macros::Route;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Mode {
     Hash,
     History,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+struct Config {
+    mode: Mode,
+    base: String,
+    hash_prefix: String,
+    restore_scroll: bool,
+    intercept_links: bool,
+}
+
+thread_local! {
+    // Set by `Router::subscribe`, so that `link`, `push` and `replace` don't need to be told, and
+    // restored when it's unsubscribed.
+    static CONFIG: RefCell<Config> = RefCell::new(Config {
+        mode: Mode::History,
+        base: String::new(),
+        hash_prefix: String::new(),
+        restore_scroll: false,
+        intercept_links: false,
+    });
+    // The last URL sent by the `Router`.
+    static CURRENT: RefCell<Option<Url>> = RefCell::new(None);
+    // The index of the current history entry.
+    static INDEX: Cell<i32> = Cell::new(0);
+    // The last scroll position of every history entry, with `restore_scroll`.
+    static SCROLL: RefCell<HashMap<i32, Position>> = RefCell::new(HashMap::new());
+}
+
+impl Config {
+    // `location` is the path, query and hash, as in `location.href` without the origin.
+    fn url(&self, location: &str) -> Url {
+        match self.route(location) {
+            Some(url) => url,
+            None if self.mode == Mode::Hash => Url::new(""),
+            None => Url::new(location),
+        }
+    }
+
+    // `None` if `location` is outside the app, i.e. outside `base` or without a hash.
+    fn route(&self, location: &str) -> Option<Url> {
+        match self.mode {
+            Mode::Hash => {
+                let hash = location.splitn(2, '#').nth(1)?;
+                Some(Url::new(
+                    strip_prefix(hash, &self.hash_prefix).unwrap_or(hash),
+                ))
+            }
+            Mode::History => strip_prefix(location, &self.base)
+                .filter(|rest| rest.is_empty() || rest.starts_with(|c| "/?#".contains(c)))
+                .map(Url::new),
+        }
+    }
+
+    fn href(&self, url: &Url) -> String {
+        match self.mode {
+            Mode::Hash => format!("#{}{}", self.hash_prefix, url),
+            Mode::History => format!("{}{}", self.base, url),
+        }
+    }
+}
+
+fn strip_prefix<'a>(str: &'a str, prefix: &str) -> Option<&'a str> {
+    if str.starts_with(prefix) {
+        Some(&str[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+// Only one `Router` should be subscribed at a time: `link`, `push`, `replace` and `href` use the
+// config of the last one subscribed, until it's unsubscribed. Apps mounted side by side have to
+// share a single `Router`, or only route one of them.
 #[derive(Debug)]
 pub struct Router {
-    mode: Mode,
+    config: Config,
 }
 
 impl Router {
     pub fn new(mode: Mode) -> Self {
-        Router { mode }
+        Router {
+            config: Config {
+                mode,
+                base: String::new(),
+                hash_prefix: String::new(),
+                restore_scroll: false,
+                intercept_links: false,
+            },
+        }
+    }
+
+    // The path the app is served from in `History` mode, e.g. "/admin". It's stripped from the
+    // URLs sent by this subscription and prepended to the ones passed to `link`, `push` and
+    // `replace`.
+    pub fn base<B: Into<String>>(mut self, base: B) -> Self {
+        let base = base.into();
+        let base = base.trim_matches('/');
+        self.config.base = if base.is_empty() {
+            String::new()
+        } else {
+            format!("/{}", base)
+        };
+        self
+    }
+
+    // What comes between "#" and the URL in `Hash` mode, e.g. "!" for "#!/posts".
+    pub fn hash_prefix<P: Into<String>>(mut self, prefix: P) -> Self {
+        self.config.hash_prefix = prefix.into().trim_start_matches('#').into();
+        self
+    }
+
+    // Scrolls to the top after `push`, and back to where the user was on back/forward, instead of
+    // leaving it to the browser, which restores the position before the app has re-rendered.
+    pub fn restore_scroll(mut self, restore_scroll: bool) -> Self {
+        self.config.restore_scroll = restore_scroll;
+        self
+    }
+
+    // Sends a `Location` with the state of the history entry instead of just the `Url`.
+    pub fn with_state(self) -> WithState {
+        WithState(self)
+    }
+
+    // Handles clicks on any `a` pointing inside the app like `link` does, so plain `a` tags with
+    // relative `href`s are routed without reloading the page too.
+    pub fn intercept_links(mut self, intercept_links: bool) -> Self {
+        self.config.intercept_links = intercept_links;
+        self
     }
 }
 
@@ -31,7 +167,56 @@ impl Subscription for Router {
 
     fn subscribe(self, send: subscription::Send<Self::Message>) -> Unsubscribe {
         let window = web::window().unwrap();
-        let mode = self.mode;
+        let config = self.config;
+        let previous = CONFIG.with(|cell| cell.replace(config.clone()));
+        let history = window.history().unwrap();
+        let initial = history.state().ok().and_then(|state| index(&state));
+        if initial.is_none() {
+            history
+                .replace_state(&entry(0, &JsValue::UNDEFINED), "")
+                .unwrap();
+        }
+        INDEX.with(|cell| cell.set(initial.unwrap_or(0)));
+        let scroll = if config.restore_scroll {
+            let _ = history.set_scroll_restoration(web::ScrollRestoration::Manual);
+            Some(Scroll::new().subscribe(Rc::new(|position| {
+                let index = INDEX.with(|cell| cell.get());
+                SCROLL.with(|scroll| scroll.borrow_mut().insert(index, position));
+            })))
+        } else {
+            None
+        };
+        let restore_scroll = config.restore_scroll;
+        let links = if config.intercept_links {
+            let config = config.clone();
+            Some(
+                OnDocument::new("click").subscribe(Rc::new(move |event: web::Event| {
+                    let anchor = event
+                        .target()
+                        .and_then(|target| target.dyn_into::<web::Element>().ok())
+                        .and_then(|element| element.closest("a[href]").ok())
+                        .and_then(|anchor| anchor?.dyn_into::<web::HtmlAnchorElement>().ok());
+                    let anchor = match anchor {
+                        Some(ref anchor) if link::intercept(&event, anchor) => anchor,
+                        _ => return,
+                    };
+                    let location = web::window().unwrap().location();
+                    // In `Hash` mode, only links to this page are inside the app.
+                    if config.mode == Mode::Hash
+                        && location.pathname().ok() != Some(anchor.pathname())
+                    {
+                        return;
+                    }
+                    let href = anchor.pathname() + &anchor.search() + &anchor.hash();
+                    if let Some(url) = config.route(&href) {
+                        event.prevent_default();
+                        push(url);
+                    }
+                })),
+            )
+        } else {
+            None
+        };
         let closure = Closure::wrap(Box::new(move || {
             let location = web::window()
                 .unwrap()
@@ -39,21 +224,45 @@ impl Subscription for Router {
                 .unwrap()
                 .location()
                 .unwrap();
-            let url = match mode {
-                Mode::Hash => {
-                    let mut hash = location.hash().unwrap();
-                    if !hash.is_empty() {
-                        hash.remove(0);
+            let location = location.pathname().unwrap()
+                + &location.search().unwrap()
+                + &location.hash().unwrap();
+            let url = config.url(&location);
+            let current = INDEX.with(|cell| cell.get());
+            let target = history.state().ok().and_then(|state| index(&state));
+            // `push` and `replace` have already checked the guards and updated the index, and a
+            // blocked back/forward navigation has been undone.
+            if target == Some(current) {
+                CURRENT.with(|cell| cell.replace(Some(url.clone())));
+                send(url);
+                guard::undone();
+                return;
+            }
+            // Entries without an index were added by the browser, e.g. by editing the hash.
+            let target = target.unwrap_or(current + 1);
+            let navigation = Navigation::new(url.clone(), guard::Kind::Go(target - current));
+            match guard::check(&navigation) {
+                Navigate::Allow => {
+                    INDEX.with(|cell| cell.set(target));
+                    if index(&history.state().unwrap_or(JsValue::UNDEFINED)).is_none() {
+                        let _ = history.replace_state(&entry(target, &JsValue::UNDEFINED), "");
+                    }
+                    CURRENT.with(|cell| cell.replace(Some(url.clone())));
+                    send(url);
+                    if restore_scroll {
+                        let position = SCROLL.with(|scroll| scroll.borrow().get(&target).cloned());
+                        scroll_to(position.unwrap_or(Position { x: 0.0, y: 0.0 }));
                     }
-                    hash
                 }
-                Mode::History => {
-                    location.pathname().unwrap()
-                        + &location.search().unwrap()
-                        + &location.hash().unwrap()
+                // This brings back the entry with the current index, which sends its URL again.
+                Navigate::Block => {
+                    let _ = history.go_with_delta(current - target);
+                }
+                Navigate::Redirect(url) => {
+                    INDEX.with(|cell| cell.set(target));
+                    navigate(url, true, JsValue::UNDEFINED);
                 }
-            };
-            send(Url::new(url));
+            }
         }) as Box<FnMut()>);
         (window.as_ref() as &web::EventTarget)
             .add_event_listener_with_callback("popstate", closure.as_ref().unchecked_ref())
@@ -62,50 +271,197 @@ impl Subscription for Router {
             .dispatch_event(&web::Event::new("popstate").unwrap())
             .unwrap();
 
+        let mut scroll = scroll;
+        let mut links = links;
+        let mut previous = Some(previous);
         Unsubscribe::new(move || {
+            links.take();
+            if let Some(previous) = previous.take() {
+                CONFIG.with(|cell| cell.replace(previous));
+            }
             (window.as_ref() as &web::EventTarget)
                 .remove_event_listener_with_callback("popstate", closure.as_ref().unchecked_ref())
                 .unwrap();
+            if scroll.take().is_some() {
+                let history = window.history().unwrap();
+                let _ = history.set_scroll_restoration(web::ScrollRestoration::Auto);
+            }
         })
     }
 }
 
-pub fn link<Message: Default + 'static>(mode: Mode, url: &str) -> NonKeyedElement<Message> {
-    let url = url.to_string();
-    crate::html::a()
-        .attr("href", url.clone())
-        .on("click", move |event| {
-            event.prevent_default();
-            push(mode, &url);
-            Message::default()
-        })
+// A URL along with the state of its history entry.
+#[derive(Clone, Debug)]
+pub struct Location {
+    pub url: Url,
+    pub state: JsValue,
+}
+
+impl Location {
+    // Parses a state pushed with `push_json` or `replace_json`.
+    #[cfg(feature = "serde")]
+    pub fn state_json<T: serde::de::DeserializeOwned>(&self) -> Option<T> {
+        serde_json::from_str(&self.state.as_string()?).ok()
+    }
+}
+
+#[derive(Debug)]
+pub struct WithState(Router);
+
+impl Subscription for WithState {
+    type Message = Location;
+
+    fn subscribe(self, send: subscription::Send<Self::Message>) -> Unsubscribe {
+        // The `Router` sends from its `popstate` handler, so the current entry is still the URL's.
+        self.0.subscribe(Rc::new(move |url| {
+            send(Location {
+                url,
+                state: state(),
+            })
+        }))
+    }
+}
+
+pub fn push(url: impl Into<Url>) {
+    push_with_state(url, JsValue::UNDEFINED);
+}
+
+pub fn replace(url: impl Into<Url>) {
+    replace_with_state(url, JsValue::UNDEFINED);
 }
 
-pub fn push(mode: Mode, url: &str) {
+// `state` is stored in the new history entry, and is returned by `state()` whenever that entry is
+// the current one, e.g. after going back to it.
+pub fn push_with_state(url: impl Into<Url>, state: impl Into<JsValue>) {
+    guarded(url.into(), false, state.into());
+}
+
+pub fn replace_with_state(url: impl Into<Url>, state: impl Into<JsValue>) {
+    guarded(url.into(), true, state.into());
+}
+
+// The state of the current history entry, or `undefined`.
+pub fn state() -> JsValue {
     web::window()
         .unwrap()
         .history()
         .unwrap()
-        .push_state_with_url(&JsValue::NULL, "", Some(&href(mode, url)))
-        .unwrap();
-    popstate();
+        .state()
+        .ok()
+        .filter(|entry| entry.is_object())
+        .and_then(|entry| js::Reflect::get(&entry, &"state".into()).ok())
+        .unwrap_or(JsValue::UNDEFINED)
 }
 
-pub fn replace(mode: Mode, url: &str) {
-    web::window()
+// Parses a state pushed with `push_json` or `replace_json`.
+#[cfg(feature = "serde")]
+pub fn state_json<T: serde::de::DeserializeOwned>() -> Option<T> {
+    serde_json::from_str(&state().as_string()?).ok()
+}
+
+// Stores `state` as a JSON string, to be read back with `state_json` or `Location::state_json`.
+#[cfg(feature = "serde")]
+pub fn push_json<T: serde::Serialize>(
+    url: impl Into<Url>,
+    state: &T,
+) -> Result<(), serde_json::Error> {
+    push_with_state(url, serde_json::to_string(state)?);
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+pub fn replace_json<T: serde::Serialize>(
+    url: impl Into<Url>,
+    state: &T,
+) -> Result<(), serde_json::Error> {
+    replace_with_state(url, serde_json::to_string(state)?);
+    Ok(())
+}
+
+pub fn back() {
+    go(-1);
+}
+
+pub fn forward() {
+    go(1);
+}
+
+// Like the browser's back and forward buttons, these go through the guards once the `Router`
+// receives the `popstate` event.
+pub fn go(delta: i32) {
+    let _ = web::window()
         .unwrap()
         .history()
         .unwrap()
-        .replace_state_with_url(&JsValue::NULL, "", Some(&href(mode, url)))
-        .unwrap();
+        .go_with_delta(delta);
+}
+
+fn guarded(url: Url, replace: bool, state: JsValue) {
+    let kind = if replace {
+        guard::Kind::Replace(state.clone())
+    } else {
+        guard::Kind::Push(state.clone())
+    };
+    match guard::check(&Navigation::new(url.clone(), kind)) {
+        Navigate::Allow => navigate(url, replace, state),
+        Navigate::Block => {}
+        Navigate::Redirect(url) => navigate(url, replace, JsValue::UNDEFINED),
+    }
+}
+
+fn navigate(url: Url, replace: bool, state: JsValue) {
+    let history = web::window().unwrap().history().unwrap();
+    let index = INDEX.with(|cell| {
+        if !replace {
+            cell.set(cell.get() + 1);
+        }
+        cell.get()
+    });
+    let entry = entry(index, &state);
+    if replace {
+        history.replace_state_with_url(&entry, "", Some(&href(&url)))
+    } else {
+        history.push_state_with_url(&entry, "", Some(&href(&url)))
+    }
+    .unwrap();
     popstate();
+    if !replace && CONFIG.with(|config| config.borrow().restore_scroll) {
+        SCROLL.with(|scroll| scroll.borrow_mut().remove(&index));
+        scroll_to(Position { x: 0.0, y: 0.0 });
+    }
 }
 
-fn href(mode: Mode, href: &str) -> String {
-    match mode {
-        Mode::Hash => String::from("#") + href,
-        Mode::History => href.to_string(),
+// Every history entry created by the router stores its index, so that a blocked back/forward
+// navigation can be undone with `history.go`, along with the app's state.
+fn entry(index: i32, state: &JsValue) -> JsValue {
+    let entry = js::Object::new();
+    js::Reflect::set(&entry, &"index".into(), &index.into()).unwrap();
+    js::Reflect::set(&entry, &"state".into(), state).unwrap();
+    entry.into()
+}
+
+fn index(entry: &JsValue) -> Option<i32> {
+    if !entry.is_object() {
+        return None;
     }
+    let index = js::Reflect::get(entry, &"index".into()).ok()?;
+    index.as_f64().map(|index| index as i32)
+}
+
+fn scroll_to(position: Position) {
+    web::window()
+        .unwrap()
+        .scroll_to_with_x_and_y(position.x, position.y);
+}
+
+// The last URL sent by the `Router`, e.g. to highlight links to it.
+pub fn current() -> Option<Url> {
+    CURRENT.with(|cell| cell.borrow().clone())
+}
+
+// The `href` for `url` with the current `Router`'s mode, base and hash prefix.
+pub fn href(url: &Url) -> String {
+    CONFIG.with(|config| config.borrow().href(url))
 }
 
 fn popstate() {
@@ -113,3 +469,30 @@ fn popstate() {
         .dispatch_event(&web::Event::new("popstate").unwrap())
         .unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Mode, Router, Url};
+
+    #[test]
+    fn config() {
+        let history = Router::new(Mode::History).base("admin/").config;
+        assert_eq!(
+            history.url("/admin/posts?page=2"),
+            Url::new("/posts?page=2")
+        );
+        assert_eq!(history.url("/admin"), Url::new("/"));
+        assert_eq!(history.url("/admin#top"), Url::new("/#top"));
+        assert_eq!(history.url("/administrator"), Url::new("/administrator"));
+        assert_eq!(history.route("/administrator"), None);
+        assert_eq!(history.route("/admin/"), Some(Url::new("/")));
+        assert_eq!(history.href(&Url::new("/posts#top")), "/admin/posts#top");
+
+        let hash = Router::new(Mode::Hash).hash_prefix("#!").config;
+        assert_eq!(hash.url("/app/#!/posts/1?a=b"), Url::new("/posts/1?a=b"));
+        assert_eq!(hash.url("/app/#/posts/1"), Url::new("/posts/1"));
+        assert_eq!(hash.url("/app/"), Url::new("/"));
+        assert_eq!(hash.route("/app/"), None);
+        assert_eq!(hash.href(&Url::path(&["posts", "1"])), "#!/posts/1");
+    }
+}
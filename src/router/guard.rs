@@ -0,0 +1,190 @@
+use super::Url;
+use crate::subscription::{BeforeUnload, Subscription, Unsubscribe};
+use crate::Mailbox;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::JsValue;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Navigate {
+    Allow,
+    Block,
+    Redirect(Url),
+}
+
+// A navigation checked by the guards, which can be repeated with `resume` once it's blocked.
+#[derive(Clone, Debug)]
+pub struct Navigation {
+    pub url: Url,
+    kind: Kind,
+}
+
+#[derive(Clone, Debug)]
+pub(super) enum Kind {
+    Push(JsValue),
+    Replace(JsValue),
+    Go(i32),
+}
+
+impl Navigation {
+    pub(super) fn new(url: Url, kind: Kind) -> Self {
+        Navigation { url, kind }
+    }
+
+    // The history delta of a back/forward navigation, `None` for `push` and `replace`.
+    pub fn delta(&self) -> Option<i32> {
+        match self.kind {
+            Kind::Go(delta) => Some(delta),
+            _ => None,
+        }
+    }
+
+    // Starts the navigation again, with `go(delta)` for back/forward so that no new history entry
+    // is added. This goes through the guards again, so drop the blocking `Guard` first.
+    pub fn resume(self) {
+        match self.kind {
+            Kind::Push(state) => super::push_with_state(self.url, state),
+            Kind::Replace(state) => super::replace_with_state(self.url, state),
+            Kind::Go(delta) => super::go(delta),
+        }
+    }
+}
+
+type GuardFn = Fn(&Navigation) -> Navigate;
+
+thread_local! {
+    static GUARDS: RefCell<Vec<(usize, Rc<GuardFn>)>> = RefCell::new(Vec::new());
+    static NEXT_ID: Cell<usize> = Cell::new(0);
+    // Messages from `block` for back/forward navigations, which are held back until the router
+    // has undone the navigation, so that `resume` starts from the entry the user was on.
+    static UNDOING: RefCell<Vec<Box<FnOnce()>>> = RefCell::new(Vec::new());
+}
+
+// Removes its hook when dropped, like `Unsubscribe`.
+#[derive(Debug)]
+pub struct Guard {
+    id: usize,
+    unload: Option<Unsubscribe>,
+}
+
+impl Guard {
+    // Also asks the user to confirm before the tab is closed or reloaded. Browsers only show their
+    // own dialog for this.
+    pub fn confirm_unload(mut self) -> Self {
+        self.unload = Some(BeforeUnload::new().subscribe(Rc::new(|()| {})));
+        self
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        let id = self.id;
+        GUARDS.with(|guards| guards.borrow_mut().retain(|(guard, _)| *guard != id));
+    }
+}
+
+// Runs `f` with the URL before every `push`, `replace`, `link` click and back/forward
+// navigation while the returned `Guard` is alive. Blocked back/forward navigations are undone.
+pub fn before_navigate(f: impl Fn(&Url) -> Navigate + 'static) -> Guard {
+    guard(move |navigation| f(&navigation.url))
+}
+
+// Blocks all navigation and sends `f(navigation)` instead, e.g. for a form with unsaved changes.
+// The app can then show its own confirm dialog, and drop the `Guard` and `navigation.resume()` if
+// the user agrees. For back/forward the message is only sent once the router has gone back to the
+// entry the user was on, so `resume` can be called straight away.
+pub fn block<Message: 'static>(
+    mailbox: &Mailbox<Message>,
+    f: impl Fn(Navigation) -> Message + 'static,
+) -> Guard {
+    blocking(mailbox, f).confirm_unload()
+}
+
+fn blocking<Message: 'static>(
+    mailbox: &Mailbox<Message>,
+    f: impl Fn(Navigation) -> Message + 'static,
+) -> Guard {
+    let mailbox = mailbox.clone();
+    guard(move |navigation| {
+        let message = f(navigation.clone());
+        if navigation.delta().is_some() {
+            let mailbox = mailbox.clone();
+            UNDOING.with(|undoing| {
+                undoing
+                    .borrow_mut()
+                    .push(Box::new(move || mailbox.send(message)))
+            });
+        } else {
+            mailbox.send(message);
+        }
+        Navigate::Block
+    })
+}
+
+fn guard(f: impl Fn(&Navigation) -> Navigate + 'static) -> Guard {
+    let id = NEXT_ID.with(|next| next.replace(next.get() + 1));
+    GUARDS.with(|guards| guards.borrow_mut().push((id, Rc::new(f))));
+    Guard { id, unload: None }
+}
+
+// Called by the router once a blocked back/forward navigation has been undone.
+pub(super) fn undone() {
+    let undoing = UNDOING.with(|undoing| undoing.replace(Vec::new()));
+    for send in undoing {
+        send();
+    }
+}
+
+// The first guard which doesn't allow `navigation` decides.
+pub(super) fn check(navigation: &Navigation) -> Navigate {
+    // The guards may add or drop guards themselves, so they can't be borrowed while they run.
+    let guards = GUARDS.with(|guards| {
+        guards
+            .borrow()
+            .iter()
+            .map(|(_, guard)| guard.clone())
+            .collect::<Vec<_>>()
+    });
+    for guard in guards {
+        match guard(navigation) {
+            Navigate::Allow => {}
+            navigate => return navigate,
+        }
+    }
+    Navigate::Allow
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{blocking, check, undone, Kind, Navigate, Navigation};
+    use crate::router::Url;
+    use crate::Mailbox;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn block() {
+        let sent = Rc::new(RefCell::new(Vec::new()));
+        let mailbox = Mailbox::new({
+            let sent = sent.clone();
+            move |navigation: Navigation| sent.borrow_mut().push(navigation)
+        });
+        let guard = blocking(&mailbox, |navigation| navigation);
+
+        // The back button: the browser is already on the previous entry, so the app only hears
+        // about it once the router has gone forward again.
+        let back = Navigation::new(Url::new("/posts"), Kind::Go(-1));
+        assert_eq!(check(&back), Navigate::Block);
+        assert!(sent.borrow().is_empty());
+        undone();
+        assert_eq!(
+            sent.borrow_mut().pop().and_then(|back| back.delta()),
+            Some(-1)
+        );
+
+        drop(guard);
+        assert_eq!(check(&back), Navigate::Allow);
+        undone();
+        assert!(sent.borrow().is_empty());
+    }
+}
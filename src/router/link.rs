@@ -0,0 +1,64 @@
+use super::{current, href, push, Url};
+use crate::NonKeyedElement;
+use wasm_bindgen::JsCast;
+use web_sys as web;
+
+// An `a` which navigates with `push` on plain left clicks, and is left to the browser otherwise,
+// e.g. for Ctrl/Cmd-clicks, middle clicks or `target="_blank"`.
+pub fn link<Message: 'static>(url: impl Into<Url>) -> NonKeyedElement<Message> {
+    let url = url.into();
+    crate::html::a()
+        .attr("href", href(&url))
+        .on_maybe("click", move |event| {
+            let anchor = event
+                .current_target()
+                .and_then(|target| target.dyn_into::<web::HtmlAnchorElement>().ok());
+            if let Some(anchor) = anchor {
+                if intercept(&event, &anchor) {
+                    event.prevent_default();
+                    push(url.clone());
+                }
+            }
+            None
+        })
+}
+
+// A `link` with an `active` class (and `aria-current="page"`) while the current URL's path starts
+// with `url`'s path.
+pub fn nav_link<Message: 'static>(url: impl Into<Url>) -> NonKeyedElement<Message> {
+    let url = url.into();
+    let active = is_active(&url, false);
+    let link = link(url);
+    if active {
+        link.class("active").attr("aria-current", "page")
+    } else {
+        link
+    }
+}
+
+// With `exact`, the current URL's path must be the same as `url`'s. Otherwise, any path under it
+// matches too, except for "/", which would match everything.
+pub fn is_active(url: &Url, exact: bool) -> bool {
+    current().map_or(false, |current| {
+        if exact || url.path.is_empty() {
+            current.path == url.path
+        } else {
+            current.path.starts_with(&url.path)
+        }
+    })
+}
+
+// Whether a click on `anchor` should be handled by the router instead of the browser.
+pub(super) fn intercept(event: &web::Event, anchor: &web::HtmlAnchorElement) -> bool {
+    let event = match event.dyn_ref::<web::MouseEvent>() {
+        Some(event) => event,
+        None => return false,
+    };
+    let location = web::window().unwrap().location();
+    !event.default_prevented()
+        && event.button() == 0
+        && !(event.ctrl_key() || event.meta_key() || event.shift_key() || event.alt_key())
+        && (anchor.target().is_empty() || anchor.target() == "_self")
+        && !anchor.has_attribute("download")
+        && location.origin().ok() == Some(anchor.origin())
+}
@@ -0,0 +1,219 @@
+use crate::fetch::{Abort, Error, Pending};
+use crate::{mailbox, Mailbox};
+use futures::Future;
+use std::cell::Cell;
+use std::rc::Rc;
+use wasm_bindgen::JsValue;
+
+// Implemented by route types whose pages need data, e.g. from `fetch`. wasm-bindgen can't split a
+// route's code into a separate module, so only the data is loaded lazily.
+pub trait Load {
+    type Data: 'static;
+
+    // `None` for routes without anything to load. Loads which aren't requests can be wrapped with
+    // `Pending::new`, with `on_abort` to stop them when they're cancelled.
+    fn load(&self) -> Option<Pending<Self::Data>>;
+}
+
+#[derive(Debug)]
+pub enum Status<T> {
+    Idle,
+    Loading,
+    Loaded(T),
+    Failed(Error),
+}
+
+// Sent when a load finishes; pass it to `Loader::loaded` in `update`.
+#[derive(Debug)]
+pub struct Loaded<R: Load> {
+    generation: usize,
+    result: Result<R::Data, Error>,
+}
+
+// Holds the current route and the status of its data. Every navigation aborts the previous
+// route's load, which then doesn't send anything.
+#[derive(Debug)]
+pub struct Loader<R: Load> {
+    route: Option<R>,
+    status: Status<R::Data>,
+    generation: Rc<Cell<usize>>,
+    abort: Option<Abort>,
+}
+
+impl<R: Load> Loader<R> {
+    pub fn new() -> Self {
+        Loader {
+            route: None,
+            status: Status::Idle,
+            generation: Rc::new(Cell::new(0)),
+            abort: None,
+        }
+    }
+
+    pub fn navigate<Message: 'static>(
+        &mut self,
+        mailbox: &Mailbox<Message>,
+        route: R,
+        f: impl Fn(Loaded<R>) -> Message + 'static,
+    ) {
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+        // Dropping the handle aborts the previous load, if it's still running.
+        self.abort = None;
+        let pending = route.load();
+        self.route = Some(route);
+        let pending = match pending {
+            Some(pending) => pending,
+            None => {
+                self.status = Status::Idle;
+                return;
+            }
+        };
+        self.status = Status::Loading;
+        self.abort = Some(pending.abort_handle());
+        let current = self.generation.clone();
+        let mailbox = mailbox.clone();
+        mailbox::spawn(pending.then(move |result| {
+            if current.get() == generation {
+                mailbox.send(f(Loaded { generation, result }));
+            }
+            Ok(JsValue::UNDEFINED)
+        }));
+    }
+
+    // Loads of routes which have since been navigated away from are ignored.
+    pub fn loaded(&mut self, loaded: Loaded<R>) {
+        if loaded.generation != self.generation.get() {
+            return;
+        }
+        self.status = match loaded.result {
+            Ok(data) => Status::Loaded(data),
+            Err(error) => Status::Failed(error),
+        };
+    }
+
+    pub fn route(&self) -> Option<&R> {
+        self.route.as_ref()
+    }
+
+    pub fn status(&self) -> &Status<R::Data> {
+        &self.status
+    }
+
+    pub fn is_loading(&self) -> bool {
+        match self.status {
+            Status::Loading => true,
+            _ => false,
+        }
+    }
+
+    pub fn data(&self) -> Option<&R::Data> {
+        match &self.status {
+            Status::Loaded(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    pub fn error(&self) -> Option<&Error> {
+        match &self.status {
+            Status::Failed(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl<R: Load> Default for Loader<R> {
+    fn default() -> Self {
+        Loader::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Load, Loaded, Loader};
+    use crate::fetch::{Error, Pending};
+    use crate::{mailbox, Mailbox};
+    use futures::sync::oneshot;
+    use futures::{future, Future};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Debug, PartialEq)]
+    enum Route {
+        Index,
+        Post(u32),
+    }
+
+    thread_local! {
+        static LOADING: RefCell<Vec<(u32, oneshot::Sender<String>)>> = RefCell::new(Vec::new());
+        static ABORTED: RefCell<Vec<u32>> = RefCell::new(Vec::new());
+    }
+
+    // Posts finish loading once the test completes them.
+    impl Load for Route {
+        type Data = String;
+
+        fn load(&self) -> Option<Pending<String>> {
+            match *self {
+                Route::Index => None,
+                Route::Post(0) => Some(Pending::new(future::err(Error::Status {
+                    code: 404,
+                    body: String::new(),
+                }))),
+                Route::Post(id) => {
+                    let (sender, receiver) = oneshot::channel();
+                    LOADING.with(|loading| loading.borrow_mut().push((id, sender)));
+                    let pending = Pending::new(receiver.map_err(|_| Error::Aborted));
+                    Some(
+                        pending.on_abort(move || {
+                            ABORTED.with(|aborted| aborted.borrow_mut().push(id))
+                        }),
+                    )
+                }
+            }
+        }
+    }
+
+    fn complete(id: u32) {
+        let sender = LOADING.with(|loading| {
+            let mut loading = loading.borrow_mut();
+            let index = loading.iter().position(|(post, _)| *post == id).unwrap();
+            loading.remove(index).1
+        });
+        sender.send(format!("post {}", id)).unwrap();
+        mailbox::run();
+    }
+
+    #[test]
+    fn loader() {
+        let messages = Rc::new(RefCell::new(Vec::<Loaded<Route>>::new()));
+        let mailbox = Mailbox::new({
+            let messages = messages.clone();
+            move |loaded| messages.borrow_mut().push(loaded)
+        });
+        let mut loader = Loader::new();
+        let navigate = |loader: &mut Loader<Route>, route| {
+            loader.navigate(&mailbox, route, |loaded| loaded);
+            assert!(loader.is_loading());
+        };
+
+        navigate(&mut loader, Route::Post(1));
+        navigate(&mut loader, Route::Post(2));
+        assert_eq!(ABORTED.with(|aborted| aborted.borrow().clone()), vec![1]);
+        // The first load finishes after navigating to the second post, so nothing is sent for it.
+        complete(1);
+        assert!(messages.borrow().is_empty());
+        complete(2);
+        loader.loaded(messages.borrow_mut().pop().unwrap());
+        assert_eq!(loader.route(), Some(&Route::Post(2)));
+        assert_eq!(loader.data(), Some(&"post 2".to_string()));
+
+        navigate(&mut loader, Route::Post(0));
+        loader.loaded(messages.borrow_mut().pop().unwrap());
+        assert_eq!(loader.error().and_then(Error::status), Some(404));
+
+        loader.navigate(&mailbox, Route::Index, |loaded| loaded);
+        assert!(!loader.is_loading() && loader.data().is_none());
+        assert!(messages.borrow().is_empty());
+    }
+}
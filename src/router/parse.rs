@@ -1,4 +1,6 @@
 use crate::router::Url;
+#[cfg(target_arch = "wasm32")]
+use js_sys as js;
 use std::marker::PhantomData;
 use std::str::FromStr;
 
@@ -29,6 +31,21 @@ pub trait Parse {
     {
         Optional(self)
     }
+
+    fn map<T, F: Fn(Self::Output) -> T>(self, f: F) -> Map<Self, F>
+    where
+        Self: Sized,
+    {
+        Map(self, f)
+    }
+
+    // Like `map`, but the parser fails if `f` returns `None`.
+    fn and_then<T, F: Fn(Self::Output) -> Option<T>>(self, f: F) -> AndThen<Self, F>
+    where
+        Self: Sized,
+    {
+        AndThen(self, f)
+    }
 }
 
 impl Parse for str {
@@ -123,6 +140,154 @@ impl<T: FromStr> Parse for Hash<T> {
     }
 }
 
+#[derive(Debug)]
+pub struct End;
+
+// Only matches once the whole path has been parsed.
+pub fn end() -> End {
+    End
+}
+
+impl Parse for End {
+    type Output = ();
+
+    fn parse_state(&self, state: &mut State) -> Option<Self::Output> {
+        if state.index == state.url.path.len() {
+            Some(())
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct QueryAll<'a, T: FromStr>(&'a str, PhantomData<T>);
+
+// Parses every value of a repeated key, e.g. `?tag=a&tag=b`. Matches no values too.
+pub fn query_all<T: FromStr>(name: &str) -> QueryAll<T> {
+    QueryAll(name, PhantomData)
+}
+
+impl<'a, T: FromStr> Parse for QueryAll<'a, T> {
+    type Output = Vec<T>;
+
+    fn parse_state(&self, state: &mut State) -> Option<Self::Output> {
+        state
+            .url
+            .query
+            .iter()
+            .filter(|(k, _)| k == self.0)
+            .map(|(_, v)| v.parse().ok())
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct QueryOpt<'a, T: FromStr>(&'a str, PhantomData<T>);
+
+// `None` if the key is missing. Unlike `query(name).optional()`, a value which doesn't parse
+// doesn't match, so `.map(|page| page.unwrap_or(1))` only defaults missing values.
+pub fn query_opt<T: FromStr>(name: &str) -> QueryOpt<T> {
+    QueryOpt(name, PhantomData)
+}
+
+impl<'a, T: FromStr> Parse for QueryOpt<'a, T> {
+    type Output = Option<T>;
+
+    fn parse_state(&self, state: &mut State) -> Option<Self::Output> {
+        match state.url.query.iter().find(|(k, _)| k == self.0) {
+            Some((_, value)) => value.parse().ok().map(Some),
+            None => Some(None),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Rest;
+
+// Parses all the remaining segments, e.g. the file's path in `/files/docs/2019/report.pdf`.
+pub fn rest() -> Rest {
+    Rest
+}
+
+impl Parse for Rest {
+    type Output = Vec<String>;
+
+    fn parse_state(&self, state: &mut State) -> Option<Self::Output> {
+        let rest = state.url.path[state.index..].to_vec();
+        state.index = state.url.path.len();
+        Some(rest)
+    }
+}
+
+#[derive(Debug)]
+pub struct Segment<F: Fn(&str) -> bool>(F);
+
+// Parses one segment for which `f` returns true.
+pub fn segment<F: Fn(&str) -> bool>(f: F) -> Segment<F> {
+    Segment(f)
+}
+
+impl<F: Fn(&str) -> bool> Parse for Segment<F> {
+    type Output = String;
+
+    fn parse_state(&self, state: &mut State) -> Option<Self::Output> {
+        let segment = state.url.path.get(state.index)?;
+        if (self.0)(segment) {
+            state.index += 1;
+            Some(segment.clone())
+        } else {
+            None
+        }
+    }
+}
+
+// `Regex` uses the browser's `RegExp`, so unlike the other parsers it's only available on wasm32,
+// and can't be used in `cargo test`.
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug)]
+pub struct Regex(Option<js::RegExp>);
+
+// Parses one segment which matches the whole of `pattern`. An invalid `pattern` never matches.
+#[cfg(target_arch = "wasm32")]
+pub fn regex(pattern: &str) -> Regex {
+    Regex(crate::regexp::new(&format!("^(?:{})$", pattern), "u"))
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Parse for Regex {
+    type Output = String;
+
+    fn parse_state(&self, state: &mut State) -> Option<Self::Output> {
+        let regexp = self.0.as_ref()?;
+        segment(|segment| regexp.test(segment)).parse_state(state)
+    }
+}
+
+#[derive(Debug)]
+pub struct Nest<F>(F);
+
+// Hands the rest of the path, along with the query and hash, to a child module's parser as a
+// `Url` of its own, e.g. `("admin", nest(admin::Route::new))`.
+pub fn nest<T, F: Fn(&Url) -> Option<T>>(f: F) -> Nest<F> {
+    Nest(f)
+}
+
+impl<T, F: Fn(&Url) -> Option<T>> Parse for Nest<F> {
+    type Output = T;
+
+    fn parse_state(&self, state: &mut State) -> Option<Self::Output> {
+        let url = Url {
+            path: state.url.path[state.index..].to_vec(),
+            query: state.url.query.clone(),
+            hash: state.url.hash.clone(),
+        };
+        let t = (self.0)(&url)?;
+        state.index = state.url.path.len();
+        Some(t)
+    }
+}
+
 impl Parse for () {
     type Output = ();
 
@@ -179,6 +344,33 @@ impl<T: Parse> Parse for Optional<T> {
     }
 }
 
+#[derive(Debug)]
+pub struct Map<P: Parse, F>(P, F);
+
+impl<P: Parse, F: Fn(P::Output) -> T, T> Parse for Map<P, F> {
+    type Output = T;
+
+    fn parse_state(&self, state: &mut State) -> Option<Self::Output> {
+        self.0.parse_state(state).map(&self.1)
+    }
+}
+
+#[derive(Debug)]
+pub struct AndThen<P: Parse, F>(P, F);
+
+impl<P: Parse, F: Fn(P::Output) -> Option<T>, T> Parse for AndThen<P, F> {
+    type Output = T;
+
+    fn parse_state(&self, state: &mut State) -> Option<Self::Output> {
+        let cloned = state.clone();
+        let t = self.0.parse_state(state).and_then(&self.1);
+        if t.is_none() {
+            *state = cloned;
+        }
+        t
+    }
+}
+
 #[derive(Debug)]
 pub struct Parser<'a, T> {
     url: &'a Url,
@@ -207,3 +399,92 @@ impl<'a, T> Parser<'a, T> {
 pub fn parse<T>(url: &Url) -> Parser<T> {
     Parser::new(url)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::router::url::tests::{rng, string};
+    use crate::router::*;
+    use rand::Rng;
+
+    #[test]
+    fn round_trip() {
+        let mut rng = rng();
+        for _ in 0..1000 {
+            let name = string(&mut rng, 1);
+            let id = rng.gen::<u32>();
+            let sort = string(&mut rng, 0);
+            let section = string(&mut rng, 1);
+            let url = Url::path(&[name.clone(), "posts".into(), id.to_string()])
+                .query("sort", &sort)
+                .hash(section.clone());
+            let url = Url::new(url.to_string());
+            let parser = (
+                param::<String>(),
+                "posts",
+                param::<u32>(),
+                query::<String>("sort"),
+                hash::<String>(),
+            );
+            assert_eq!(
+                parser.parse(&url),
+                Some((name, (), id, sort, section)),
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn alt() {
+        let route = |url: &str| {
+            parse(&Url::new(url))
+                .alt((), |()| 0)
+                .alt(("posts", query::<u32>("page").optional()), |((), page)| {
+                    page.unwrap_or(1)
+                })
+                .value()
+        };
+        assert_eq!(route("/"), Some(0));
+        assert_eq!(route("/posts"), Some(1));
+        assert_eq!(route("/posts?page=3"), Some(3));
+        assert_eq!(route("/posts/1"), None);
+    }
+
+    #[test]
+    fn parsers() {
+        let url = Url::new("/files/docs/2019/a%20b.pdf?tag=x&tag=y&page=2&n=abc");
+        assert_eq!(
+            ("files", rest()).parse(&url),
+            Some(((), vec!["docs".into(), "2019".into(), "a b.pdf".into()]))
+        );
+        assert_eq!(
+            (
+                "files",
+                nest(|url| ("docs", param::<u32>(), rest()).parse(url))
+            )
+                .parse(&url),
+            Some(((), ((), 2019, vec!["a b.pdf".into()])))
+        );
+        assert_eq!(
+            (query_all::<String>("tag"), query_all::<u32>("none"), rest()).parse(&url),
+            Some((vec!["x".into(), "y".into()], vec![], url.path.clone()))
+        );
+        assert_eq!((query_all::<u32>("tag"), rest()).parse(&url), None);
+        assert_eq!(
+            (query_opt::<u32>("page"), query_opt::<u32>("size"), rest()).parse(&url),
+            Some((Some(2), None, url.path.clone()))
+        );
+        assert_eq!((query_opt::<u32>("n"), rest()).parse(&url), None);
+
+        let year = segment(|segment| segment.len() == 4).and_then(|year| year.parse::<u32>().ok());
+        assert_eq!(
+            ("files", "docs", year.map(|year| year + 1), rest()).parse(&url),
+            Some(((), (), 2020, vec!["a b.pdf".into()]))
+        );
+        let year = segment(|segment| segment.len() == 4).and_then(|year| year.parse::<u32>().ok());
+        assert_eq!(
+            ("files", year.optional(), rest()).parse(&url).map(|t| t.1),
+            Some(None)
+        );
+    }
+}
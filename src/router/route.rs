@@ -0,0 +1,31 @@
+use super::parse::{Parse, State};
+use super::Url;
+use std::marker::PhantomData;
+
+// Implemented for enums by `#[derive(Route)]` (with the `derive` feature) from the `#[at("...")]`
+// attribute on each variant, so that parsing and generating URLs can't drift apart.
+pub trait Route: Sized {
+    fn parse_state(state: &mut State) -> Option<Self>;
+
+    fn to_url(&self) -> Url;
+
+    fn from_url(url: &Url) -> Option<Self> {
+        nested::<Self>().parse(url)
+    }
+}
+
+#[derive(Debug)]
+pub struct Nested<T: Route>(PhantomData<T>);
+
+// Parses the rest of the URL as `T`, e.g. `("admin", nested::<AdminRoute>())`.
+pub fn nested<T: Route>() -> Nested<T> {
+    Nested(PhantomData)
+}
+
+impl<T: Route> Parse for Nested<T> {
+    type Output = T;
+
+    fn parse_state(&self, state: &mut State) -> Option<Self::Output> {
+        T::parse_state(state)
+    }
+}
@@ -1,3 +1,7 @@
+use crate::fetch::encode;
+use std::fmt;
+
+// Path segments, query keys and values and the hash are stored percent-decoded.
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub struct Url {
     pub path: Vec<String>,
@@ -13,14 +17,19 @@ impl Url {
         let path = path
             .split('/')
             .filter(|str| *str != "")
-            .map(Into::into)
+            .map(|str| decode(str, false))
             .collect();
         let query = query
             .split('&')
+            .filter(|part| *part != "")
             .map(|part| split(part, '='))
-            .map(|(k, v)| (k.into(), v.into()))
+            .map(|(k, v)| (decode(k, true), decode(v, true)))
             .collect();
-        let hash = if hash == "" { None } else { Some(hash.into()) };
+        let hash = if hash == "" {
+            None
+        } else {
+            Some(decode(hash, false))
+        };
 
         return Url { path, query, hash };
 
@@ -29,4 +38,168 @@ impl Url {
             (splitted.next().unwrap(), splitted.next().unwrap_or(""))
         }
     }
+
+    // Builds a URL from unencoded segments, e.g. `Url::path(&["posts", &id.to_string()])`.
+    pub fn path<I>(segments: I) -> Url
+    where
+        I: IntoIterator,
+        I::Item: ToString,
+    {
+        Url {
+            path: segments
+                .into_iter()
+                .map(|segment| segment.to_string())
+                .collect(),
+            query: Vec::new(),
+            hash: None,
+        }
+    }
+
+    pub fn query<K: Into<String>, V: ToString>(mut self, key: K, value: V) -> Url {
+        self.query.push((key.into(), value.to_string()));
+        self
+    }
+
+    pub fn hash<H: Into<String>>(mut self, hash: H) -> Url {
+        self.hash = Some(hash.into());
+        self
+    }
+}
+
+impl<'a> From<&'a str> for Url {
+    fn from(str: &'a str) -> Self {
+        Url::new(str)
+    }
+}
+
+impl From<String> for Url {
+    fn from(string: String) -> Self {
+        Url::new(string)
+    }
+}
+
+// Percent-encodes everything back, so `Url::new(url.to_string()) == url`.
+impl fmt::Display for Url {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("/")?;
+        for (index, segment) in self.path.iter().enumerate() {
+            if index > 0 {
+                f.write_str("/")?;
+            }
+            f.write_str(&encode(segment))?;
+        }
+        for (index, (key, value)) in self.query.iter().enumerate() {
+            f.write_str(if index == 0 { "?" } else { "&" })?;
+            write!(f, "{}={}", encode(key), encode(value))?;
+        }
+        if let Some(hash) = &self.hash {
+            write!(f, "#{}", encode(hash))?;
+        }
+        Ok(())
+    }
+}
+
+// Invalid escapes are kept as they are. In query strings, `+` is a space.
+fn decode(str: &str, plus: bool) -> String {
+    let bytes = str.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'%' => match (hex(bytes.get(index + 1)), hex(bytes.get(index + 2))) {
+                (Some(high), Some(low)) => {
+                    decoded.push(high * 16 + low);
+                    index += 3;
+                    continue;
+                }
+                _ => decoded.push(b'%'),
+            },
+            b'+' if plus => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+    return String::from_utf8_lossy(&decoded).into_owned();
+
+    fn hex(byte: Option<&u8>) -> Option<u8> {
+        (*byte? as char).to_digit(16).map(|digit| digit as u8)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::Url;
+    use rand::prng::XorShiftRng;
+    use rand::{Rng, SeedableRng};
+
+    pub(crate) fn rng() -> XorShiftRng {
+        XorShiftRng::from_seed([42; 16])
+    }
+
+    // Mostly characters that need encoding, or that mean something in a URL.
+    pub(crate) fn string<R: Rng>(rng: &mut R, min: usize) -> String {
+        let chars = [
+            'a', 'Z', '0', '-', '.', '~', ' ', '/', '?', '#', '&', '=', '+', '%', ':', '\'', 'é',
+            '漢', '🦀',
+        ];
+        let len = rng.gen_range(min, 8);
+        (0..len).map(|_| *rng.choose(&chars).unwrap()).collect()
+    }
+
+    #[test]
+    fn decode() {
+        let url = Url::new("/caf%C3%A9/a%2Fb?q=hello%20world+again&a+b=%ZZ&&x#top%20");
+        assert_eq!(url.path, ["café", "a/b"]);
+        assert_eq!(
+            url.query,
+            [
+                ("q".into(), "hello world again".into()),
+                ("a b".into(), "%ZZ".into()),
+                ("x".into(), "".into()),
+            ]
+        );
+        assert_eq!(url.hash, Some("top ".into()));
+        assert_eq!(Url::new("/posts?").query, []);
+        assert_eq!(Url::new("/posts/1%2B1").path, ["posts", "1+1"]);
+    }
+
+    #[test]
+    fn to_string() {
+        let url = Url::new("/caf%C3%A9/a%2Fb?q=hello+world&k=a%26b%3Dc#top");
+        assert_eq!(
+            url.to_string(),
+            "/caf%C3%A9/a%2Fb?q=hello%20world&k=a%26b%3Dc#top"
+        );
+        assert_eq!(Url::new(url.to_string()), url);
+        assert_eq!(Url::new("").to_string(), "/");
+    }
+
+    #[test]
+    fn builder() {
+        let url = Url::path(&["posts", "1 2"])
+            .query("page", 2)
+            .query("q", "a&b")
+            .hash("comments");
+        assert_eq!(url.to_string(), "/posts/1%202?page=2&q=a%26b#comments");
+        assert_eq!(Url::new(url.to_string()), url);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut rng = rng();
+        for _ in 0..1000 {
+            let mut url = Url::path((0..rng.gen_range(0, 4)).map(|_| string(&mut rng, 1)));
+            for _ in 0..rng.gen_range(0, 4) {
+                url = url.query(string(&mut rng, 0), string(&mut rng, 0));
+            }
+            if rng.gen() {
+                url = url.hash(string(&mut rng, 1));
+            }
+            assert_eq!(Url::new(url.to_string()), url, "{}", url);
+
+            // Parsing any string, even with invalid escapes, and printing it again is stable.
+            let url = Url::new(string(&mut rng, 0) + &string(&mut rng, 0));
+            assert_eq!(Url::new(url.to_string()), url, "{}", url);
+        }
+    }
 }
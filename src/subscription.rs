@@ -1,15 +1,57 @@
+use crate::timer;
 use js_sys as js;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use web_sys as web;
 
+pub mod combinators;
+pub mod event_source;
+pub mod websocket;
+pub mod window;
+
+pub use self::combinators::{Debounce, Filter, Map, Throttle};
+pub use self::event_source::EventSource;
+pub use self::websocket::WebSocket;
+pub use self::window::{BeforeUnload, KeyboardShortcut, Online, Scroll, Shortcut, WindowSize};
+
 pub type Send<Message> = Rc<Fn(Message)>;
 
 pub trait Subscription {
     type Message;
 
     fn subscribe(self, send: Send<Self::Message>) -> Unsubscribe;
+
+    fn map<T, F: Fn(Self::Message) -> T + 'static>(self, f: F) -> Map<Self, F>
+    where
+        Self: Sized,
+    {
+        Map::new(self, f)
+    }
+
+    fn filter<F: Fn(&Self::Message) -> bool + 'static>(self, f: F) -> Filter<Self, F>
+    where
+        Self: Sized,
+    {
+        Filter::new(self, f)
+    }
+
+    // Only sends a message once `ms` milliseconds have passed without a newer one.
+    fn debounce(self, ms: i32) -> Debounce<Self>
+    where
+        Self: Sized,
+    {
+        Debounce::new(self, ms)
+    }
+
+    // Sends at most one message every `ms` milliseconds, always including the last one.
+    fn throttle(self, ms: i32) -> Throttle<Self>
+    where
+        Self: Sized,
+    {
+        Throttle::new(self, ms)
+    }
 }
 
 pub struct Unsubscribe(Option<Box<FnMut()>>);
@@ -34,14 +76,79 @@ impl Drop for Unsubscribe {
     }
 }
 
+#[derive(Clone, Copy, Debug, Default)]
+struct Options {
+    capture: bool,
+    passive: bool,
+}
+
+macro_rules! options {
+    ($($ident:ident)+) => {
+        $(
+            impl $ident {
+                pub fn capture(mut self, capture: bool) -> Self {
+                    self.options.capture = capture;
+                    self
+                }
+
+                pub fn passive(mut self, passive: bool) -> Self {
+                    self.options.passive = passive;
+                    self
+                }
+            }
+        )+
+    }
+}
+
+options! {
+    OnWindow OnDocument OnElement MediaQuery
+}
+
+fn listen(
+    target: web::EventTarget,
+    name: String,
+    options: Options,
+    closure: Closure<FnMut(web::Event)>,
+) -> Unsubscribe {
+    let mut add_options = web::AddEventListenerOptions::new();
+    add_options.capture(options.capture);
+    add_options.passive(options.passive);
+    target
+        .add_event_listener_with_callback_and_add_event_listener_options(
+            &name,
+            closure.as_ref().unchecked_ref(),
+            &add_options,
+        )
+        .unwrap();
+    Unsubscribe::new(move || {
+        target
+            .remove_event_listener_with_callback_and_bool(
+                &name,
+                closure.as_ref().unchecked_ref(),
+                options.capture,
+            )
+            .unwrap();
+    })
+}
+
+fn forward(send: Send<web::Event>) -> Closure<FnMut(web::Event)> {
+    Closure::wrap(Box::new(move |event: web::Event| {
+        send(event);
+    }) as Box<FnMut(web::Event)>)
+}
+
 #[derive(Debug)]
 pub struct OnWindow {
     name: String,
+    options: Options,
 }
 
 impl OnWindow {
     pub fn new<N: Into<String>>(name: N) -> Self {
-        OnWindow { name: name.into() }
+        OnWindow {
+            name: name.into(),
+            options: Options::default(),
+        }
     }
 }
 
@@ -50,17 +157,128 @@ impl Subscription for OnWindow {
 
     fn subscribe(self, send: Send<Self::Message>) -> Unsubscribe {
         let window = web::window().unwrap();
-        let closure = Closure::wrap(Box::new(move |event: web::Event| {
-            send(event);
+        listen(window.into(), self.name, self.options, forward(send))
+    }
+}
+
+#[derive(Debug)]
+pub struct OnDocument {
+    name: String,
+    options: Options,
+}
+
+impl OnDocument {
+    pub fn new<N: Into<String>>(name: N) -> Self {
+        OnDocument {
+            name: name.into(),
+            options: Options::default(),
+        }
+    }
+}
+
+impl Subscription for OnDocument {
+    type Message = web::Event;
+
+    fn subscribe(self, send: Send<Self::Message>) -> Unsubscribe {
+        let document = web::window().unwrap().document().unwrap();
+        listen(document.into(), self.name, self.options, forward(send))
+    }
+}
+
+#[derive(Debug)]
+pub enum Target {
+    Selector(String),
+    Element(web::Element),
+}
+
+impl<'a> From<&'a str> for Target {
+    fn from(selector: &'a str) -> Self {
+        Target::Selector(selector.into())
+    }
+}
+
+impl From<String> for Target {
+    fn from(selector: String) -> Self {
+        Target::Selector(selector)
+    }
+}
+
+impl From<web::Element> for Target {
+    fn from(element: web::Element) -> Self {
+        Target::Element(element)
+    }
+}
+
+#[derive(Debug)]
+pub struct OnElement {
+    target: Target,
+    name: String,
+    options: Options,
+}
+
+impl OnElement {
+    pub fn new<T: Into<Target>, N: Into<String>>(target: T, name: N) -> Self {
+        OnElement {
+            target: target.into(),
+            name: name.into(),
+            options: Options::default(),
+        }
+    }
+}
+
+impl Subscription for OnElement {
+    type Message = web::Event;
+
+    fn subscribe(self, send: Send<Self::Message>) -> Unsubscribe {
+        let element = match self.target {
+            Target::Selector(selector) => match crate::select(&selector) {
+                Some(element) => element,
+                // The element may not have been rendered yet, which isn't worth crashing the app
+                // over, so this only logs it.
+                None => {
+                    crate::console::error(&format!("no element matches {:?}", selector));
+                    return Unsubscribe::new(|| {});
+                }
+            },
+            Target::Element(element) => element,
+        };
+        listen(element.into(), self.name, self.options, forward(send))
+    }
+}
+
+// Sends whether the media query matches right away, and then again whenever that changes.
+#[derive(Debug)]
+pub struct MediaQuery {
+    query: String,
+    options: Options,
+}
+
+impl MediaQuery {
+    pub fn new<Q: Into<String>>(query: Q) -> Self {
+        MediaQuery {
+            query: query.into(),
+            options: Options::default(),
+        }
+    }
+}
+
+impl Subscription for MediaQuery {
+    type Message = bool;
+
+    fn subscribe(self, send: Send<Self::Message>) -> Unsubscribe {
+        let list = web::window()
+            .unwrap()
+            .match_media(&self.query)
+            .unwrap()
+            .expect("match_media");
+        send(list.matches());
+        let closure = Closure::wrap(Box::new({
+            let list = list.clone();
+            move |_: web::Event| {
+                send(list.matches());
+            }
         }) as Box<FnMut(web::Event)>);
-        (window.as_ref() as &web::EventTarget)
-            .add_event_listener_with_callback(&self.name, closure.as_ref().unchecked_ref())
-            .unwrap();
-        Unsubscribe::new(move || {
-            (window.as_ref() as &web::EventTarget)
-                .remove_event_listener_with_callback(&self.name, closure.as_ref().unchecked_ref())
-                .unwrap();
-        })
+        listen(list.into(), "change".into(), self.options, closure)
     }
 }
 
@@ -97,3 +315,88 @@ impl Subscription for Interval {
         })
     }
 }
+
+// Sends `()` once after `ms` milliseconds, unless it's unsubscribed from before that.
+#[derive(Debug)]
+pub struct Timeout {
+    ms: i32,
+}
+
+impl Timeout {
+    pub fn new(ms: i32) -> Self {
+        Timeout { ms }
+    }
+}
+
+impl Subscription for Timeout {
+    type Message = ();
+
+    fn subscribe(self, send: Send<Self::Message>) -> Unsubscribe {
+        let timeout = timer::Timeout::new(self.ms, move || send(()));
+        let mut timeout = Some(timeout);
+        Unsubscribe::new(move || {
+            timeout.take();
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frame {
+    // Milliseconds since the page was loaded, as passed to `requestAnimationFrame` callbacks.
+    pub timestamp: f64,
+    // Milliseconds since the previous frame, or 0 for the first one.
+    pub delta: f64,
+}
+
+// Sends a `Frame` before every repaint, in sync with the display's refresh rate.
+#[derive(Debug, Default)]
+pub struct AnimationFrame;
+
+impl AnimationFrame {
+    pub fn new() -> Self {
+        AnimationFrame
+    }
+}
+
+impl Subscription for AnimationFrame {
+    type Message = Frame;
+
+    fn subscribe(self, send: Send<Self::Message>) -> Unsubscribe {
+        let window = web::window().unwrap();
+        let id = Rc::new(Cell::new(None));
+        // The closure requests the next frame with itself, so it only holds a weak reference to
+        // itself and is freed as soon as `Unsubscribe` drops the strong one.
+        let closure = Rc::new(RefCell::new(None::<Closure<FnMut(f64)>>));
+        let request = {
+            let window = window.clone();
+            let id = id.clone();
+            let closure = Rc::downgrade(&closure);
+            move || {
+                if let Some(closure) = closure.upgrade() {
+                    if let Some(closure) = &*closure.borrow() {
+                        let callback = closure.as_ref().unchecked_ref();
+                        id.set(window.request_animation_frame(callback).ok());
+                    }
+                }
+            }
+        };
+        let mut previous = None;
+        closure.replace(Some(Closure::wrap(Box::new({
+            let request = request.clone();
+            move |timestamp: f64| {
+                let delta = previous.map_or(0.0, |previous| timestamp - previous);
+                previous = Some(timestamp);
+                // The next frame is requested first so that unsubscribing in `send` cancels it.
+                request();
+                send(Frame { timestamp, delta });
+            }
+        }) as Box<FnMut(f64)>)));
+        request();
+        Unsubscribe::new(move || {
+            if let Some(id) = id.take() {
+                let _ = window.cancel_animation_frame(id);
+            }
+            closure.replace(None);
+        })
+    }
+}
@@ -0,0 +1,156 @@
+use super::{Send, Subscription, Unsubscribe};
+use crate::timer;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+#[derive(Debug)]
+pub struct Map<S, F> {
+    subscription: S,
+    f: F,
+}
+
+impl<S, F> Map<S, F> {
+    pub(super) fn new(subscription: S, f: F) -> Self {
+        Map { subscription, f }
+    }
+}
+
+impl<S, F, T> Subscription for Map<S, F>
+where
+    S: Subscription,
+    T: 'static,
+    F: Fn(S::Message) -> T + 'static,
+{
+    type Message = T;
+
+    fn subscribe(self, send: Send<Self::Message>) -> Unsubscribe {
+        let f = self.f;
+        self.subscription
+            .subscribe(Rc::new(move |message| send(f(message))))
+    }
+}
+
+#[derive(Debug)]
+pub struct Filter<S, F> {
+    subscription: S,
+    f: F,
+}
+
+impl<S, F> Filter<S, F> {
+    pub(super) fn new(subscription: S, f: F) -> Self {
+        Filter { subscription, f }
+    }
+}
+
+impl<S, F> Subscription for Filter<S, F>
+where
+    S: Subscription,
+    S::Message: 'static,
+    F: Fn(&S::Message) -> bool + 'static,
+{
+    type Message = S::Message;
+
+    fn subscribe(self, send: Send<Self::Message>) -> Unsubscribe {
+        let f = self.f;
+        self.subscription.subscribe(Rc::new(move |message| {
+            if f(&message) {
+                send(message);
+            }
+        }))
+    }
+}
+
+#[derive(Debug)]
+pub struct Debounce<S> {
+    subscription: S,
+    ms: i32,
+}
+
+impl<S> Debounce<S> {
+    pub(super) fn new(subscription: S, ms: i32) -> Self {
+        Debounce { subscription, ms }
+    }
+}
+
+impl<S> Subscription for Debounce<S>
+where
+    S: Subscription,
+    S::Message: 'static,
+{
+    type Message = S::Message;
+
+    fn subscribe(self, send: Send<Self::Message>) -> Unsubscribe {
+        let ms = self.ms;
+        let timeout = Rc::new(RefCell::new(None));
+        let unsubscribe = self.subscription.subscribe(Rc::new({
+            let timeout = timeout.clone();
+            move |message| {
+                let send = send.clone();
+                let mut message = Some(message);
+                // This clears the previous timeout, if it hasn't fired yet.
+                timeout.replace(Some(timer::Timeout::new(ms, move || {
+                    if let Some(message) = message.take() {
+                        send(message);
+                    }
+                })));
+            }
+        }));
+        let mut unsubscribe = Some(unsubscribe);
+        Unsubscribe::new(move || {
+            unsubscribe.take();
+            timeout.replace(None);
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct Throttle<S> {
+    subscription: S,
+    ms: i32,
+}
+
+impl<S> Throttle<S> {
+    pub(super) fn new(subscription: S, ms: i32) -> Self {
+        Throttle { subscription, ms }
+    }
+}
+
+impl<S> Subscription for Throttle<S>
+where
+    S: Subscription,
+    S::Message: 'static,
+{
+    type Message = S::Message;
+
+    fn subscribe(self, send: Send<Self::Message>) -> Unsubscribe {
+        let ms = self.ms;
+        let latest = Rc::new(RefCell::new(None));
+        let scheduled = Rc::new(Cell::new(false));
+        let timeout = Rc::new(RefCell::new(None));
+        let unsubscribe = self.subscription.subscribe(Rc::new({
+            let timeout = timeout.clone();
+            move |message| {
+                latest.replace(Some(message));
+                if scheduled.replace(true) {
+                    return;
+                }
+                let send = send.clone();
+                let latest = latest.clone();
+                let scheduled = scheduled.clone();
+                // This drops the previous, already fired, timeout.
+                timeout.replace(Some(timer::Timeout::new(ms, move || {
+                    scheduled.set(false);
+                    let message = latest.borrow_mut().take();
+                    if let Some(message) = message {
+                        send(message);
+                    }
+                })));
+            }
+        }));
+        let mut unsubscribe = Some(unsubscribe);
+        Unsubscribe::new(move || {
+            unsubscribe.take();
+            timeout.replace(None);
+        })
+    }
+}
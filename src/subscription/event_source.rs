@@ -0,0 +1,108 @@
+use crate::subscription::{Send, Subscription, Unsubscribe};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys as web;
+
+#[derive(Debug)]
+pub struct EventSource {
+    url: String,
+    events: Vec<String>,
+    with_credentials: bool,
+}
+
+#[derive(Debug)]
+pub enum Event {
+    Open,
+    Message(Message),
+    // The browser reconnects on its own after most errors; `Error` is sent for every failed
+    // attempt.
+    Error,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Message {
+    pub name: String,
+    pub data: String,
+    pub last_event_id: String,
+}
+
+impl EventSource {
+    pub fn new<U: Into<String>>(url: U) -> Self {
+        EventSource {
+            url: url.into(),
+            events: vec!["message".into()],
+            with_credentials: false,
+        }
+    }
+
+    // Listens for events sent with `event: <name>` in addition to unnamed `message` events.
+    pub fn event<N: Into<String>>(mut self, name: N) -> Self {
+        self.events.push(name.into());
+        self
+    }
+
+    pub fn with_credentials(mut self, with_credentials: bool) -> Self {
+        self.with_credentials = with_credentials;
+        self
+    }
+}
+
+impl Subscription for EventSource {
+    type Message = Event;
+
+    fn subscribe(self, send: Send<Self::Message>) -> Unsubscribe {
+        let mut init = web::EventSourceInit::new();
+        init.with_credentials(self.with_credentials);
+        // The constructor throws for an invalid URL, which is reported like a failed connection.
+        let source = match web::EventSource::new_with_event_source_init_dict(&self.url, &init) {
+            Ok(source) => source,
+            Err(_) => {
+                send(Event::Error);
+                return Unsubscribe::new(|| {});
+            }
+        };
+
+        let on_message = Closure::wrap(Box::new({
+            let send = send.clone();
+            move |event: web::Event| {
+                let event = event.unchecked_into::<web::MessageEvent>();
+                send(Event::Message(Message {
+                    name: event.type_(),
+                    data: event.data().as_string().unwrap_or_default(),
+                    last_event_id: event.last_event_id(),
+                }));
+            }
+        }) as Box<FnMut(web::Event)>);
+        let on_open = Closure::wrap(Box::new({
+            let send = send.clone();
+            move |_: web::Event| send(Event::Open)
+        }) as Box<FnMut(web::Event)>);
+        let on_error = Closure::wrap(Box::new(move |_: web::Event| {
+            send(Event::Error);
+        }) as Box<FnMut(web::Event)>);
+
+        let listeners = self
+            .events
+            .iter()
+            .map(|name| (name.as_str(), &on_message))
+            .chain(vec![("open", &on_open), ("error", &on_error)])
+            .map(|(name, closure)| (name.to_string(), closure.as_ref().clone()))
+            .collect::<Vec<(String, JsValue)>>();
+        for (name, callback) in &listeners {
+            (source.as_ref() as &web::EventTarget)
+                .add_event_listener_with_callback(name, callback.unchecked_ref())
+                .unwrap();
+        }
+
+        Unsubscribe::new(move || {
+            // We need to move the closures here so that they aren't dropped too early.
+            let _ = (&on_message, &on_open, &on_error);
+            for (name, callback) in &listeners {
+                (source.as_ref() as &web::EventTarget)
+                    .remove_event_listener_with_callback(name, callback.unchecked_ref())
+                    .unwrap();
+            }
+            source.close();
+        })
+    }
+}
@@ -0,0 +1,238 @@
+use crate::fetch;
+use crate::subscription::{Send, Subscription, Unsubscribe};
+use crate::timer;
+use js_sys as js;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys as web;
+
+#[derive(Debug)]
+pub struct WebSocket {
+    url: String,
+    protocols: Vec<String>,
+    reconnect: Option<(i32, i32)>,
+    socket: Rc<RefCell<Option<web::WebSocket>>>,
+}
+
+#[derive(Debug)]
+pub enum Event {
+    Open,
+    Message(Data),
+    Close { code: u16, reason: String },
+    Error,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Data {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+#[cfg(feature = "serde")]
+impl Data {
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        match self {
+            Data::Text(text) => serde_json::from_str(text),
+            Data::Binary(bytes) => serde_json::from_slice(bytes),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Sender {
+    socket: Rc<RefCell<Option<web::WebSocket>>>,
+}
+
+impl Sender {
+    pub fn is_open(&self) -> bool {
+        self.socket
+            .borrow()
+            .as_ref()
+            .map_or(false, |socket| socket.ready_state() == web::WebSocket::OPEN)
+    }
+
+    pub fn send_text(&self, text: &str) -> Result<(), JsValue> {
+        self.with_socket(|socket| socket.send_with_str(text))
+    }
+
+    pub fn send_bytes(&self, bytes: &[u8]) -> Result<(), JsValue> {
+        self.with_socket(|socket| socket.send_with_array_buffer_view(&fetch::uint8_array(bytes)))
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn send_json<T: serde::Serialize>(&self, value: &T) -> Result<(), JsValue> {
+        let text =
+            serde_json::to_string(value).map_err(|error| JsValue::from_str(&error.to_string()))?;
+        self.send_text(&text)
+    }
+
+    fn with_socket(
+        &self,
+        f: impl FnOnce(&web::WebSocket) -> Result<(), JsValue>,
+    ) -> Result<(), JsValue> {
+        match &*self.socket.borrow() {
+            Some(socket) => f(socket),
+            None => Err(JsValue::from_str("WebSocket is not connected")),
+        }
+    }
+}
+
+impl WebSocket {
+    pub fn new<U: Into<String>>(url: U) -> Self {
+        WebSocket {
+            url: url.into(),
+            protocols: Vec::new(),
+            reconnect: None,
+            socket: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    pub fn protocol<P: Into<String>>(mut self, protocol: P) -> Self {
+        self.protocols.push(protocol.into());
+        self
+    }
+
+    // Reconnects after the socket closes, waiting `delay` ms at first and doubling the wait after
+    // every failed attempt, up to `max_delay` ms.
+    pub fn reconnect(mut self, delay: i32, max_delay: i32) -> Self {
+        self.reconnect = Some((delay, max_delay));
+        self
+    }
+
+    pub fn sender(&self) -> Sender {
+        Sender {
+            socket: self.socket.clone(),
+        }
+    }
+}
+
+struct Inner {
+    url: String,
+    protocols: Vec<String>,
+    reconnect: Option<(i32, i32)>,
+    socket: Rc<RefCell<Option<web::WebSocket>>>,
+    send: Send<Event>,
+    closures: RefCell<Vec<Closure<FnMut(web::Event)>>>,
+    timeout: RefCell<Option<timer::Timeout>>,
+    attempt: Cell<i32>,
+    unsubscribed: Cell<bool>,
+}
+
+impl Inner {
+    fn connect(inner: &Rc<Self>) {
+        let socket = if inner.protocols.is_empty() {
+            web::WebSocket::new(&inner.url)
+        } else {
+            let protocols = inner
+                .protocols
+                .iter()
+                .map(|protocol| JsValue::from_str(protocol))
+                .collect::<js::Array>();
+            web::WebSocket::new_with_str_sequence(&inner.url, &protocols)
+        };
+        let socket = match socket {
+            Ok(socket) => socket,
+            Err(_) => {
+                (inner.send)(Event::Error);
+                return;
+            }
+        };
+        socket.set_binary_type(web::BinaryType::Arraybuffer);
+
+        let onopen = Inner::closure(inner, |inner, _| {
+            inner.attempt.set(0);
+            (inner.send)(Event::Open);
+        });
+        let onmessage = Inner::closure(inner, |inner, event| {
+            let data = event.unchecked_into::<web::MessageEvent>().data();
+            let data = match data.as_string() {
+                Some(text) => Data::Text(text),
+                None => Data::Binary(fetch::bytes(&js::Uint8Array::new(&data))),
+            };
+            (inner.send)(Event::Message(data));
+        });
+        let onclose = Inner::closure(inner, |inner, event| {
+            let event = event.unchecked_into::<web::CloseEvent>();
+            (inner.send)(Event::Close {
+                code: event.code(),
+                reason: event.reason(),
+            });
+            Inner::schedule_reconnect(inner);
+        });
+        let onerror = Inner::closure(inner, |inner, _| (inner.send)(Event::Error));
+        socket.set_onopen(Some(onopen.as_ref().unchecked_ref()));
+        socket.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+        socket.set_onclose(Some(onclose.as_ref().unchecked_ref()));
+        socket.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+
+        inner
+            .closures
+            .replace(vec![onopen, onmessage, onclose, onerror]);
+        inner.socket.replace(Some(socket));
+    }
+
+    fn closure(
+        inner: &Rc<Self>,
+        f: impl Fn(&Rc<Self>, web::Event) + 'static,
+    ) -> Closure<FnMut(web::Event)> {
+        let inner = Rc::clone(inner);
+        Closure::wrap(Box::new(move |event| f(&inner, event)) as Box<FnMut(web::Event)>)
+    }
+
+    fn schedule_reconnect(inner: &Rc<Self>) {
+        let (delay, max_delay) = match inner.reconnect {
+            Some(reconnect) => reconnect,
+            None => return,
+        };
+        if inner.unsubscribed.get() {
+            return;
+        }
+        let attempt = inner.attempt.get();
+        inner.attempt.set(attempt + 1);
+        let delay = delay.saturating_mul(1 << attempt.min(16)).min(max_delay);
+        let cloned = Rc::clone(inner);
+        // The old socket's closures are still running here, so they're only replaced once the
+        // timeout fires.
+        inner
+            .timeout
+            .replace(Some(timer::Timeout::new(delay, move || {
+                Inner::connect(&cloned)
+            })));
+    }
+
+    fn close(&self) {
+        self.unsubscribed.set(true);
+        self.timeout.replace(None);
+        if let Some(socket) = self.socket.borrow_mut().take() {
+            socket.set_onopen(None);
+            socket.set_onmessage(None);
+            socket.set_onclose(None);
+            socket.set_onerror(None);
+            let _ = socket.close();
+        }
+        // This also breaks the reference cycle between `Inner` and its closures.
+        self.closures.replace(Vec::new());
+    }
+}
+
+impl Subscription for WebSocket {
+    type Message = Event;
+
+    fn subscribe(self, send: Send<Self::Message>) -> Unsubscribe {
+        let inner = Rc::new(Inner {
+            url: self.url,
+            protocols: self.protocols,
+            reconnect: self.reconnect,
+            socket: self.socket,
+            send,
+            closures: RefCell::new(Vec::new()),
+            timeout: RefCell::new(None),
+            attempt: Cell::new(0),
+            unsubscribed: Cell::new(false),
+        });
+        Inner::connect(&inner);
+        Unsubscribe::new(move || inner.close())
+    }
+}
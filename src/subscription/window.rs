@@ -0,0 +1,351 @@
+use super::{listen, Options, Send, Subscription, Unsubscribe};
+use std::str::FromStr;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys as web;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Size {
+    pub width: f64,
+    pub height: f64,
+}
+
+// Sends the window's inner size right away, and then again after every `resize`.
+#[derive(Debug, Default)]
+pub struct WindowSize {
+    throttle: Option<i32>,
+}
+
+impl WindowSize {
+    pub fn new() -> Self {
+        WindowSize::default()
+    }
+
+    // Sends at most one size every `ms` milliseconds, always including the last one.
+    pub fn throttle(mut self, ms: i32) -> Self {
+        self.throttle = Some(ms);
+        self
+    }
+}
+
+fn size() -> Size {
+    let window = web::window().unwrap();
+    Size {
+        width: window
+            .inner_width()
+            .ok()
+            .and_then(|width| width.as_f64())
+            .unwrap_or(0.0),
+        height: window
+            .inner_height()
+            .ok()
+            .and_then(|height| height.as_f64())
+            .unwrap_or(0.0),
+    }
+}
+
+impl Subscription for WindowSize {
+    type Message = Size;
+
+    fn subscribe(self, send: Send<Self::Message>) -> Unsubscribe {
+        send(size());
+        match self.throttle {
+            None => Resize.subscribe(send),
+            Some(ms) => Resize.throttle(ms).subscribe(send),
+        }
+    }
+}
+
+// Only the `resize` events, so that the initial size isn't throttled.
+struct Resize;
+
+impl Subscription for Resize {
+    type Message = Size;
+
+    fn subscribe(self, send: Send<Self::Message>) -> Unsubscribe {
+        let closure = Closure::wrap(Box::new(move |_: web::Event| {
+            send(size());
+        }) as Box<FnMut(web::Event)>);
+        let window = web::window().unwrap();
+        listen(window.into(), "resize".into(), Options::default(), closure)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+}
+
+// Sends the window's scroll position right away, and then again after every `scroll`.
+#[derive(Debug, Default)]
+pub struct Scroll;
+
+impl Scroll {
+    pub fn new() -> Self {
+        Scroll
+    }
+}
+
+fn position() -> Position {
+    let window = web::window().unwrap();
+    Position {
+        x: window.scroll_x().unwrap_or(0.0),
+        y: window.scroll_y().unwrap_or(0.0),
+    }
+}
+
+impl Subscription for Scroll {
+    type Message = Position;
+
+    fn subscribe(self, send: Send<Self::Message>) -> Unsubscribe {
+        send(position());
+        let closure = Closure::wrap(Box::new(move |_: web::Event| {
+            send(position());
+        }) as Box<FnMut(web::Event)>);
+        let window = web::window().unwrap();
+        let options = Options {
+            passive: true,
+            ..Options::default()
+        };
+        listen(window.into(), "scroll".into(), options, closure)
+    }
+}
+
+// Sends whether the browser is online right away, and then again whenever that changes.
+#[derive(Debug, Default)]
+pub struct Online;
+
+impl Online {
+    pub fn new() -> Self {
+        Online
+    }
+}
+
+impl Subscription for Online {
+    type Message = bool;
+
+    fn subscribe(self, send: Send<Self::Message>) -> Unsubscribe {
+        let window = web::window().unwrap();
+        send(window.navigator().on_line());
+        let online = Closure::wrap(Box::new({
+            let send = send.clone();
+            move |_: web::Event| send(true)
+        }) as Box<FnMut(web::Event)>);
+        let offline = Closure::wrap(Box::new(move |_: web::Event| {
+            send(false);
+        }) as Box<FnMut(web::Event)>);
+        let online = listen(
+            window.clone().into(),
+            "online".into(),
+            Options::default(),
+            online,
+        );
+        let offline = listen(window.into(), "offline".into(), Options::default(), offline);
+        Unsubscribe::new(move || {
+            let _ = (&online, &offline);
+        })
+    }
+}
+
+// While subscribed, the browser asks the user to confirm before closing or reloading the tab.
+// `()` is sent whenever that happens.
+#[derive(Debug, Default)]
+pub struct BeforeUnload;
+
+impl BeforeUnload {
+    pub fn new() -> Self {
+        BeforeUnload
+    }
+}
+
+impl Subscription for BeforeUnload {
+    type Message = ();
+
+    fn subscribe(self, send: Send<Self::Message>) -> Unsubscribe {
+        let closure = Closure::wrap(Box::new(move |event: web::Event| {
+            event.prevent_default();
+            // Some browsers only show the dialog if `returnValue` is set.
+            event
+                .unchecked_into::<web::BeforeUnloadEvent>()
+                .set_return_value("");
+            send(());
+        }) as Box<FnMut(web::Event)>);
+        let window = web::window().unwrap();
+        listen(
+            window.into(),
+            "beforeunload".into(),
+            Options::default(),
+            closure,
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Shortcut {
+    pub key: String,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub meta: bool,
+}
+
+impl FromStr for Shortcut {
+    type Err = String;
+
+    // Parses shortcuts like "Ctrl+K", "Shift+Alt+ArrowUp" or "Ctrl++". "Mod" is Cmd on macOS and
+    // Ctrl everywhere else.
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let mut shortcut = Shortcut::default();
+        let (modifiers, key) = if str.ends_with("++") || str == "+" {
+            (&str[..str.len() - 1], "+")
+        } else {
+            match str.rfind('+') {
+                Some(index) => (&str[..=index], &str[index + 1..]),
+                None => ("", str),
+            }
+        };
+        for modifier in modifiers.split('+').filter(|modifier| !modifier.is_empty()) {
+            let flag = match &*modifier.to_lowercase() {
+                "ctrl" | "control" => &mut shortcut.ctrl,
+                "alt" | "option" => &mut shortcut.alt,
+                "shift" => &mut shortcut.shift,
+                "meta" | "cmd" | "command" | "super" => &mut shortcut.meta,
+                "mod" if is_mac() => &mut shortcut.meta,
+                "mod" => &mut shortcut.ctrl,
+                _ => return Err(format!("unknown modifier {:?} in {:?}", modifier, str)),
+            };
+            *flag = true;
+        }
+        shortcut.key = match &*key.to_lowercase() {
+            "" => return Err(format!("missing key in {:?}", str)),
+            "esc" => "escape".into(),
+            "space" => " ".into(),
+            "up" | "down" | "left" | "right" => format!("arrow{}", key.to_lowercase()),
+            "del" => "delete".into(),
+            key => key.into(),
+        };
+        Ok(shortcut)
+    }
+}
+
+impl Shortcut {
+    pub fn matches(&self, event: &web::KeyboardEvent) -> bool {
+        if event.ctrl_key() != self.ctrl
+            || event.alt_key() != self.alt
+            || event.meta_key() != self.meta
+        {
+            return false;
+        }
+        // Symbols like "+" or "?" usually need Shift, so it's only checked if it was asked for.
+        if self.shift && !event.shift_key() {
+            return false;
+        }
+        if event.key().to_lowercase() == self.key {
+            return true;
+        }
+        // With Alt (or other layouts), `key` may be a different character, so fall back to the
+        // physical key for letters and digits.
+        let code = event.code();
+        let mut chars = self.key.chars();
+        match (chars.next(), chars.next()) {
+            (Some(char), None) if char.is_ascii_alphabetic() => {
+                code == format!("Key{}", char.to_ascii_uppercase())
+            }
+            (Some(char), None) if char.is_ascii_digit() => code == format!("Digit{}", char),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn is_mac() -> bool {
+    web::window()
+        .and_then(|window| window.navigator().platform().ok())
+        .map_or(false, |platform| platform.starts_with("Mac"))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn is_mac() -> bool {
+    false
+}
+
+// Sends `()` and prevents the browser's default action whenever the shortcut is pressed.
+#[derive(Debug)]
+pub struct KeyboardShortcut {
+    shortcut: Shortcut,
+}
+
+impl KeyboardShortcut {
+    // Panics if `shortcut` can't be parsed. Parse it into a `Shortcut` first to handle the error.
+    pub fn new(shortcut: &str) -> Self {
+        KeyboardShortcut {
+            shortcut: shortcut.parse().unwrap_or_else(|error| panic!("{}", error)),
+        }
+    }
+}
+
+impl From<Shortcut> for KeyboardShortcut {
+    fn from(shortcut: Shortcut) -> Self {
+        KeyboardShortcut { shortcut }
+    }
+}
+
+impl Subscription for KeyboardShortcut {
+    type Message = ();
+
+    fn subscribe(self, send: Send<Self::Message>) -> Unsubscribe {
+        let shortcut = self.shortcut;
+        let closure = Closure::wrap(Box::new(move |event: web::Event| {
+            let event = event.unchecked_into::<web::KeyboardEvent>();
+            if shortcut.matches(&event) {
+                event.prevent_default();
+                send(());
+            }
+        }) as Box<FnMut(web::Event)>);
+        let window = web::window().unwrap();
+        listen(window.into(), "keydown".into(), Options::default(), closure)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Shortcut;
+
+    #[test]
+    fn parse() {
+        let parse = |str: &str| str.parse::<Shortcut>();
+        assert_eq!(
+            parse("Ctrl+K"),
+            Ok(Shortcut {
+                key: "k".into(),
+                ctrl: true,
+                ..Shortcut::default()
+            })
+        );
+        assert_eq!(
+            parse("shift+alt+Up"),
+            Ok(Shortcut {
+                key: "arrowup".into(),
+                alt: true,
+                shift: true,
+                ..Shortcut::default()
+            })
+        );
+        assert_eq!(
+            parse("Cmd++"),
+            Ok(Shortcut {
+                key: "+".into(),
+                meta: true,
+                ..Shortcut::default()
+            })
+        );
+        assert_eq!(
+            parse("Mod+Esc").map(|s| (s.ctrl, s.key)),
+            Ok((true, "escape".into()))
+        );
+        assert_eq!(parse("?").map(|s| s.key), Ok("?".into()));
+        assert!(parse("Hyper+K").is_err());
+        assert!(parse("Ctrl+").is_err());
+    }
+}
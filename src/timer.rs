@@ -0,0 +1,61 @@
+use futures::Future;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys as web;
+
+// A `setTimeout` which is cleared, and its closure freed, when dropped.
+pub(crate) struct Timeout {
+    id: i32,
+    _closure: Closure<FnMut()>,
+}
+
+impl Timeout {
+    pub(crate) fn new(ms: i32, f: impl FnMut() + 'static) -> Self {
+        let closure = Closure::wrap(Box::new(f) as Box<FnMut()>);
+        let id = web::window()
+            .unwrap()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                closure.as_ref().unchecked_ref(),
+                ms,
+            )
+            .unwrap();
+        Timeout {
+            id,
+            _closure: closure,
+        }
+    }
+}
+
+impl Drop for Timeout {
+    fn drop(&mut self) {
+        web::window().unwrap().clear_timeout_with_handle(self.id);
+    }
+}
+
+impl std::fmt::Debug for Timeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Timeout").field("id", &self.id).finish()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn delay(ms: i32) -> impl Future<Item = (), Error = ()> {
+    let (sender, receiver) = futures::sync::oneshot::channel();
+    let mut sender = Some(sender);
+    let timeout = Timeout::new(ms, move || {
+        if let Some(sender) = sender.take() {
+            let _ = sender.send(());
+        }
+    });
+    receiver.then(move |_| {
+        // We need to move `timeout` here so that it isn't cleared too early.
+        let _ = timeout;
+        Ok(())
+    })
+}
+
+// Outside the browser there are no timers; futures are run to completion right away instead.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn delay(_ms: i32) -> impl Future<Item = (), Error = ()> {
+    futures::future::ok(())
+}
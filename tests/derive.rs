@@ -0,0 +1,65 @@
+use draco::router::{Route, Url};
+
+#[derive(Debug, PartialEq, Route)]
+enum Page {
+    #[at("/")]
+    Index,
+    #[at("/posts")]
+    Posts { sort: Option<String>, page: u32 },
+    #[at("/posts/:id")]
+    Post(u32),
+    #[at("/posts/:id/comments/:comment")]
+    Comment {
+        comment: u32,
+        id: u32,
+        state: Option<String>,
+    },
+}
+
+#[test]
+fn round_trip() {
+    let pages = vec![
+        ("/", Page::Index),
+        (
+            "/posts?page=2",
+            Page::Posts {
+                sort: None,
+                page: 2,
+            },
+        ),
+        (
+            "/posts?page=2&sort=id",
+            Page::Posts {
+                sort: Some("id".into()),
+                page: 2,
+            },
+        ),
+        ("/posts/3", Page::Post(3)),
+        (
+            "/posts/3/comments/4?state=x",
+            Page::Comment {
+                comment: 4,
+                id: 3,
+                state: Some("x".into()),
+            },
+        ),
+    ];
+    for (url, page) in pages {
+        let parsed = Page::from_url(&Url::new(url));
+        assert_eq!(parsed, Some(page));
+        let page = parsed.unwrap();
+        assert_eq!(
+            Page::from_url(&Url::new(page.to_url().to_string())),
+            Some(page)
+        );
+    }
+    assert_eq!(Page::Post(5).to_url(), Url::path(&["posts", "5"]));
+    assert_eq!(Page::Post(5).to_url().to_string(), "/posts/5");
+}
+
+#[test]
+fn no_match() {
+    assert_eq!(Page::from_url(&Url::new("/posts")), None);
+    assert_eq!(Page::from_url(&Url::new("/posts/x")), None);
+    assert_eq!(Page::from_url(&Url::new("/posts/3/comments")), None);
+}